@@ -73,5 +73,15 @@ test_pa 'test -n "$aaa"' -o "$opt" -- --aaa
 test_pa 'test -n "$ZZZ"' -o "$opt" -- -Z
 test_pa 'test -n "$ZZZ"' -o "$opt" -- --ZZZ
 
+# the same with options read from a response file
+rsp_file="$(mktemp)" || exit 1
+for o in a b c d e f g h i j k l m n o p q r s t u v w x y z; do
+    printf -- '-%s # option %s\n' "$o" "$o"
+done > "$rsp_file"
+printf -- "'last arg'\n" >> "$rsp_file"
+
+test_pa 'test -n "$aaa" && test -n "$zzz" && test "$1" = "last arg"' --response-files -o "$opt" -- "@$rsp_file"
+rm -f "$rsp_file"
+
 end_test
 
@@ -0,0 +1,24 @@
+#!/bin/sh
+#
+# Test parseargs map options
+#
+# shellcheck disable=SC2016
+
+script_dir="$(cd "$(dirname "$0")" && pwd)" || exit 1
+script_name="$(basename "$0")"
+
+. "$script_dir/_test.shinc"
+
+start_test
+
+if shell_supports_arrays; then
+    test_pa 'test "${props[profile]}" = prod -a "${props[region]}" = "eu=west"' -o 'D=%props' -- -Dprofile=prod -D region=eu=west
+
+    # replacement markers in key and value must not break the quoting
+    test_pa 'k="k;echo PWNED #"; test "${props[$k]}" = "{KEY}"' -o 'D=%props' -- -D 'k;echo PWNED #={KEY}'
+    test_pa 'k="{VALUE}'\''"; test "${props[$k]}" = "{NAME}{KEY}"' -o 'D=%props' -- -D "{VALUE}'={NAME}{KEY}"
+else
+    echo "Skipped map option tests: Not supported with $TEST_SHELL and/or mode $PARSEARGS_SHELL"
+fi
+
+end_test
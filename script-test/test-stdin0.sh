@@ -0,0 +1,45 @@
+#!/bin/sh
+#
+# Test parseargs reading the script arguments NUL-delimited from STDIN
+#
+# shellcheck disable=SC2016
+
+script_dir="$(cd "$(dirname "$0")" && pwd)" || exit 1
+script_name="$(basename "$0")"
+
+. "$script_dir/_test.shinc"
+
+start_test
+
+# Like test_pa, but passes the script arguments NUL-delimited via STDIN
+# using the idiom from the man page.
+# $1: Code to evaluate after parseargs
+# $2: The option definition
+# $3...: The script arguments
+test_pa_stdin0()
+{
+    expect="$1"
+    opt_def="$2"
+    shift 2
+    args="$(printf "'%s' " "$@")"
+    (
+    if eval "$(for a in "$@"; do printf '%s\0' "$a"; done | parseargs --args-from-stdin0 --no-args -o "$opt_def" --)"; then
+        eval "$expect"
+    else
+        exit 1
+    fi
+    )
+    if [ "$?" = "0" ]; then
+        ok "parseargs --args-from-stdin0 -o '$opt_def' <<< $args"
+    else
+        failed "parseargs --args-from-stdin0 -o '$opt_def' <<< $args"
+    fi
+}
+
+# no arguments must not result in one empty argument
+test_pa_stdin0 'test $# -eq 0 -a -z "$verbose"' 'v#verbose'
+test_pa_stdin0 'test $# -eq 0 -a -n "$verbose"' 'v#verbose' -v
+test_pa_stdin0 'test $# -eq 0 -a "$file" = "my file.txt"' 'f=file' -f "my file.txt"
+test_pa_stdin0 'test $# -eq 0 -a -z "$file"' 'f=file' -f ""
+
+end_test
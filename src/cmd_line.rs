@@ -12,6 +12,10 @@ use std::fmt;
 pub enum CmdLineElement {
     /// A short option like '-l' without the leading dash
     ShortOption(char),
+    /// A short option like '+l' without the leading plus. Turns a flag off.
+    PlusOption(char),
+    /// A numeric option like '-20' (without the leading dash). Like `head -20`.
+    NumericOption(String),
     /// A long option like '--long' without the leading dashes
     LongOption(String),
     /// A long option (without the leading dashes) with value (from --option=value)
@@ -27,6 +31,8 @@ impl fmt::Display for CmdLineElement {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             CmdLineElement::ShortOption(c) => write!(f, "-{}", c),
+            CmdLineElement::PlusOption(c) => write!(f, "+{}", c),
+            CmdLineElement::NumericOption(n) => write!(f, "-{}", n),
             CmdLineElement::LongOption(c) => write!(f, "--{}", c),
             CmdLineElement::LongOptionValue(o, v) => write!(f, "--{}={}", o, v),
             CmdLineElement::Argument(v) => write!(f, "'{}'", v),
@@ -35,6 +41,18 @@ impl fmt::Display for CmdLineElement {
     }
 }
 
+/// Handling of options with a single dash and more than one character (`-name`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SingleDashMode {
+    /// `-name` is a cluster of short options (`-n -a -m -e`).
+    Short,
+    /// `-name` is the long option `name`. Only `-x` is a short option.
+    Long,
+    /// `-name` is the long option `name`, if such a long option exists.
+    /// Else it is a cluster of short options.
+    Mixed,
+}
+
 pub struct CmdLineTokenizer {
     /// Vector with command line arguments
     cmd_line_args: Vec<String>,
@@ -48,6 +66,18 @@ pub struct CmdLineTokenizer {
     /// Left over characters from combined short options. With -abc, this will
     /// hold ['b', 'c'].
     left_over: Vec<char>,
+    /// Whether the left over characters are from a `+abc` option.
+    left_over_plus: bool,
+    /// Whether options with a leading `+` are supported.
+    plus_options: bool,
+    /// Whether numeric options like `-20` are supported.
+    numeric_options: bool,
+    /// Handling of options like `-name`.
+    single_dash: SingleDashMode,
+    /// The known long options. Needed for `SingleDashMode::Mixed`.
+    long_options: Vec<String>,
+    /// Whether the known long options are matched case-insensitive.
+    ignore_case: bool,
 }
 
 impl CmdLineTokenizer {
@@ -58,9 +88,86 @@ impl CmdLineTokenizer {
             posix,
             args_only: false,
             left_over: Vec::new(),
+            left_over_plus: false,
+            plus_options: false,
+            numeric_options: false,
+            single_dash: SingleDashMode::Short,
+            long_options: Vec::new(),
+            ignore_case: false,
         }
     }
 
+    /// Enables options with a leading `+` (like `+x` or `+abc`).
+    pub fn set_plus_options(&mut self, plus_options: bool) {
+        self.plus_options = plus_options;
+    }
+
+    /// Enables numeric options (like `-20`). Then a dash followed by digits
+    /// is not handled as combined short options.
+    pub fn set_numeric_options(&mut self, numeric_options: bool) {
+        self.numeric_options = numeric_options;
+    }
+
+    /// Enables case-insensitive matching of the known long options.
+    pub fn set_ignore_case(&mut self, ignore_case: bool) {
+        self.ignore_case = ignore_case;
+    }
+
+    /// Sets the handling of options like `-name` and the known long options.
+    pub fn set_single_dash(&mut self, mode: SingleDashMode, long_options: Vec<String>) {
+        self.single_dash = mode;
+        self.long_options = long_options;
+    }
+
+    /// Returns the command line part that was processed last.
+    pub fn current_part(&self) -> Option<String> {
+        match self.cmd_line_args_idx {
+            0 => None,
+            idx => Some(self.cmd_line_args[idx - 1].clone()),
+        }
+    }
+
+    // Internal: returns the long option for `-name` or `-name=value` (without
+    // the leading dash), if single dash long options are enabled and apply.
+    fn single_dash_long_option(&self, opt_str: &str) -> Option<CmdLineElement> {
+        let (name, value) = match opt_str.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (opt_str, None),
+        };
+        let is_long = match self.single_dash {
+            SingleDashMode::Short => false,
+            SingleDashMode::Long => name.chars().count() > 1,
+            SingleDashMode::Mixed if self.ignore_case => {
+                let name = name.to_lowercase();
+                self.long_options.iter().any(|o| o.to_lowercase() == name)
+            }
+            SingleDashMode::Mixed => self.long_options.iter().any(|o| o == name),
+        };
+        match (is_long, value) {
+            (false, _) => None,
+            (true, Some(value)) => Some(CmdLineElement::LongOptionValue(
+                name.to_string(),
+                value.to_string(),
+            )),
+            (true, None) => Some(CmdLineElement::LongOption(name.to_string())),
+        }
+    }
+
+    /// Resumes option processing after it was stopped on the first
+    /// non-option (posix mode). Used when the first non-option selects a subcommand.
+    pub fn resume_options(&mut self, posix: bool) {
+        self.posix = posix;
+        self.args_only = false;
+    }
+
+    /// Returns all remaining parts of the command line without further processing.
+    /// Used to forward the rest of the command line to another command.
+    pub fn take_remaining(&mut self) -> Vec<String> {
+        let remaining = self.cmd_line_args[self.cmd_line_args_idx..].to_vec();
+        self.cmd_line_args_idx = self.cmd_line_args.len();
+        remaining
+    }
+
     // Internal: get next part (separated string) from the command line.
     fn next_part(&mut self) -> Option<String> {
         if self.cmd_line_args_idx >= self.cmd_line_args.len() {
@@ -77,7 +184,11 @@ impl CmdLineTokenizer {
         if !self.left_over.is_empty() {
             // next character from combined short options (-xyz)
             let chr = self.left_over.remove(0);
-            Some(CmdLineElement::ShortOption(chr))
+            if self.left_over_plus {
+                Some(CmdLineElement::PlusOption(chr))
+            } else {
+                Some(CmdLineElement::ShortOption(chr))
+            }
         } else if self.args_only {
             self.next_part().map(CmdLineElement::Argument)
         } else {
@@ -105,12 +216,33 @@ impl CmdLineTokenizer {
                         } else {
                             Some(CmdLineElement::LongOption(opt_str.to_string()))
                         }
+                    } else if let (true, Some(num)) = (
+                        self.numeric_options,
+                        s.strip_prefix('-')
+                            .filter(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit())),
+                    ) {
+                        Some(CmdLineElement::NumericOption(num.to_string()))
+                    } else if let Some(long_opt) = s
+                        .strip_prefix('-')
+                        .and_then(|opt_str| self.single_dash_long_option(opt_str))
+                    {
+                        Some(long_opt)
                     } else if let Some(opt_str) = s.strip_prefix('-') {
                         // skip leading '-'
                         let mut cs = opt_str.chars();
                         let chr = cs.next().unwrap();
                         cs.for_each(|f| self.left_over.push(f));
+                        self.left_over_plus = false;
                         Some(CmdLineElement::ShortOption(chr))
+                    } else if let (true, Some(opt_str)) =
+                        (self.plus_options && s.len() > 1, s.strip_prefix('+'))
+                    {
+                        // skip leading '+'
+                        let mut cs = opt_str.chars();
+                        let chr = cs.next().unwrap();
+                        cs.for_each(|f| self.left_over.push(f));
+                        self.left_over_plus = true;
+                        Some(CmdLineElement::PlusOption(chr))
                     } else {
                         if self.posix {
                             self.args_only = true;
@@ -122,6 +254,19 @@ impl CmdLineTokenizer {
         }
     }
 
+    /// Returns an argument for a previous option, if it is attached to the
+    /// option. This is the case for combined options like `-ooutfile`.
+    /// Never consumes the next part of the command line.
+    pub fn get_attached_option_argument(&mut self) -> Option<String> {
+        if !self.left_over.is_empty() {
+            let ret = Some(self.left_over.clone().into_iter().collect());
+            self.left_over.clear();
+            ret
+        } else {
+            None
+        }
+    }
+
     /// Returns an argument for a previous option.
     /// Also handles combined options like `-ooutfile`.
     pub fn get_option_argument(&mut self) -> Option<String> {
@@ -142,6 +287,11 @@ mod cmd_line_element_tests {
     #[test]
     fn test_to_string() {
         assert_eq!("-d", format!("{}", CmdLineElement::ShortOption('d')));
+        assert_eq!("+d", format!("{}", CmdLineElement::PlusOption('d')));
+        assert_eq!(
+            "-20",
+            format!("{}", CmdLineElement::NumericOption("20".to_string()))
+        );
         assert_eq!(
             "--debug",
             format!("{}", CmdLineElement::LongOption("debug".to_string()))
@@ -162,7 +312,7 @@ mod cmd_line_element_tests {
 }
 #[cfg(test)]
 mod arg_parser_tests {
-    use crate::cmd_line::{CmdLineElement, CmdLineTokenizer};
+    use crate::cmd_line::{CmdLineElement, CmdLineTokenizer, SingleDashMode};
 
     #[test]
     fn test_normal() {
@@ -237,6 +387,26 @@ mod arg_parser_tests {
         assert_eq!(None, pa.next());
     }
 
+    #[test]
+    fn test_attached_argument() {
+        let args = ["-cauto", "-c", "always", "-dc"].map(String::from).to_vec();
+
+        let mut pa = CmdLineTokenizer::new(args, false);
+
+        assert_eq!(Some(CmdLineElement::ShortOption('c')), pa.next());
+        assert_eq!(Some("auto".to_string()), pa.get_attached_option_argument());
+        assert_eq!(Some(CmdLineElement::ShortOption('c')), pa.next());
+        assert_eq!(None, pa.get_attached_option_argument());
+        assert_eq!(
+            Some(CmdLineElement::Argument("always".to_string())),
+            pa.next()
+        );
+        assert_eq!(Some(CmdLineElement::ShortOption('d')), pa.next());
+        assert_eq!(Some(CmdLineElement::ShortOption('c')), pa.next());
+        assert_eq!(None, pa.get_attached_option_argument());
+        assert_eq!(None, pa.next());
+    }
+
     #[test]
     fn test_dash_dash() {
         let args = ["-d", "--", "-o"].map(String::from).to_vec();
@@ -294,4 +464,166 @@ mod arg_parser_tests {
         assert_eq!(Some(CmdLineElement::Argument("two".to_string())), pa.next());
         assert_eq!(None, pa.next());
     }
+
+    #[test]
+    fn test_resume_options() {
+        let args = ["-d", "build", "-r", "one", "-x"]
+            .map(String::from)
+            .to_vec();
+
+        let mut pa = CmdLineTokenizer::new(args, true);
+
+        assert_eq!(Some(CmdLineElement::ShortOption('d')), pa.next());
+        assert_eq!(
+            Some(CmdLineElement::Argument("build".to_string())),
+            pa.next()
+        );
+        pa.resume_options(false);
+        assert_eq!(Some(CmdLineElement::ShortOption('r')), pa.next());
+        assert_eq!(Some(CmdLineElement::Argument("one".to_string())), pa.next());
+        assert_eq!(Some(CmdLineElement::ShortOption('x')), pa.next());
+        assert_eq!(None, pa.next());
+    }
+
+    #[test]
+    fn test_take_remaining() {
+        let args = ["-d", "-x", "ls", "-l", "--", "dir"]
+            .map(String::from)
+            .to_vec();
+
+        let mut pa = CmdLineTokenizer::new(args, false);
+
+        assert_eq!(Some(CmdLineElement::ShortOption('d')), pa.next());
+        assert_eq!(Some(CmdLineElement::ShortOption('x')), pa.next());
+        assert_eq!(
+            vec!["ls", "-l", "--", "dir"]
+                .into_iter()
+                .map(String::from)
+                .collect::<Vec<String>>(),
+            pa.take_remaining()
+        );
+        assert_eq!(None, pa.next());
+    }
+
+    #[test]
+    fn test_single_dash_long() {
+        let args = ["-verbose", "-output=file", "-x", "-name", "value"]
+            .map(String::from)
+            .to_vec();
+
+        let mut pa = CmdLineTokenizer::new(args, false);
+        pa.set_single_dash(SingleDashMode::Long, vec![]);
+
+        assert_eq!(
+            Some(CmdLineElement::LongOption("verbose".to_string())),
+            pa.next()
+        );
+        assert_eq!(
+            Some(CmdLineElement::LongOptionValue(
+                "output".to_string(),
+                "file".to_string()
+            )),
+            pa.next()
+        );
+        assert_eq!(Some(CmdLineElement::ShortOption('x')), pa.next());
+        assert_eq!(
+            Some(CmdLineElement::LongOption("name".to_string())),
+            pa.next()
+        );
+        assert_eq!(Some("-name".to_string()), pa.current_part());
+        assert_eq!(Some("value".to_string()), pa.get_option_argument());
+        assert_eq!(None, pa.next());
+    }
+
+    #[test]
+    fn test_single_dash_mixed() {
+        let args = ["-verbose", "-vx", "--verbose"].map(String::from).to_vec();
+
+        let mut pa = CmdLineTokenizer::new(args, false);
+        pa.set_single_dash(SingleDashMode::Mixed, vec!["verbose".to_string()]);
+
+        assert_eq!(
+            Some(CmdLineElement::LongOption("verbose".to_string())),
+            pa.next()
+        );
+        assert_eq!(Some(CmdLineElement::ShortOption('v')), pa.next());
+        assert_eq!(Some(CmdLineElement::ShortOption('x')), pa.next());
+        assert_eq!(
+            Some(CmdLineElement::LongOption("verbose".to_string())),
+            pa.next()
+        );
+        assert_eq!(None, pa.next());
+    }
+
+    #[test]
+    fn test_single_dash_mixed_ignore_case() {
+        let args = ["-Verbose", "-VERBOSE=1"].map(String::from).to_vec();
+
+        let mut pa = CmdLineTokenizer::new(args.clone(), false);
+        pa.set_single_dash(SingleDashMode::Mixed, vec!["verbose".to_string()]);
+        pa.set_ignore_case(true);
+
+        assert_eq!(
+            Some(CmdLineElement::LongOption("Verbose".to_string())),
+            pa.next()
+        );
+        assert_eq!(
+            Some(CmdLineElement::LongOptionValue(
+                "VERBOSE".to_string(),
+                "1".to_string()
+            )),
+            pa.next()
+        );
+        assert_eq!(None, pa.next());
+
+        // case-sensitive
+        let mut pa = CmdLineTokenizer::new(args, false);
+        pa.set_single_dash(SingleDashMode::Mixed, vec!["verbose".to_string()]);
+
+        assert_eq!(Some(CmdLineElement::ShortOption('V')), pa.next());
+    }
+
+    #[test]
+    fn test_plus_options() {
+        let args = ["+x", "+ab", "-cd", "+", "one"].map(String::from).to_vec();
+
+        let mut pa = CmdLineTokenizer::new(args.clone(), false);
+        pa.set_plus_options(true);
+
+        assert_eq!(Some(CmdLineElement::PlusOption('x')), pa.next());
+        assert_eq!(Some(CmdLineElement::PlusOption('a')), pa.next());
+        assert_eq!(Some(CmdLineElement::PlusOption('b')), pa.next());
+        assert_eq!(Some(CmdLineElement::ShortOption('c')), pa.next());
+        assert_eq!(Some(CmdLineElement::ShortOption('d')), pa.next());
+        assert_eq!(Some(CmdLineElement::Argument("+".to_string())), pa.next());
+        assert_eq!(Some(CmdLineElement::Argument("one".to_string())), pa.next());
+        assert_eq!(None, pa.next());
+
+        // not enabled
+        let mut pa = CmdLineTokenizer::new(args, false);
+        assert_eq!(Some(CmdLineElement::Argument("+x".to_string())), pa.next());
+    }
+
+    #[test]
+    fn test_numeric_options() {
+        let args = ["-20", "-5x", "-n", "-1"].map(String::from).to_vec();
+
+        let mut pa = CmdLineTokenizer::new(args.clone(), false);
+        pa.set_numeric_options(true);
+
+        assert_eq!(
+            Some(CmdLineElement::NumericOption("20".to_string())),
+            pa.next()
+        );
+        assert_eq!(Some(CmdLineElement::ShortOption('5')), pa.next());
+        assert_eq!(Some(CmdLineElement::ShortOption('x')), pa.next());
+        assert_eq!(Some(CmdLineElement::ShortOption('n')), pa.next());
+        assert_eq!(Some("-1".to_string()), pa.get_option_argument());
+        assert_eq!(None, pa.next());
+
+        // not enabled
+        let mut pa = CmdLineTokenizer::new(args, false);
+        assert_eq!(Some(CmdLineElement::ShortOption('2')), pa.next());
+        assert_eq!(Some(CmdLineElement::ShortOption('0')), pa.next());
+    }
 }
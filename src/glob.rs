@@ -0,0 +1,297 @@
+//
+// Part of parseargs - a command line options parser for shell scripts
+//
+// Copyright (c) 2023 Ralf Schandl
+// This code is licensed under MIT license (see LICENSE.txt for details).
+//
+
+//! Matching of shell glob patterns as used by the shell in `case` statements.
+//!
+//! Supported are:
+//!
+//! * `*` - matches any string, including the empty string
+//! * `?` - matches any single character
+//! * `[...]` - matches one of the enclosed characters. Supports ranges (`a-z`),
+//!   negation with a leading `!` or `^` and the character classes
+//!   `[:alpha:]`, `[:digit:]` etc.
+//! * `\` - escapes the next character
+//!
+//! Like in the shell, a `[` without closing `]` matches itself.
+
+/// Element of a parsed glob pattern.
+#[derive(Debug, PartialEq)]
+enum GlobElement {
+    /// A literal character
+    Char(char),
+    /// `?`
+    AnyChar,
+    /// `*`
+    AnyString,
+    /// `[...]`: the character set items and whether the set is negated
+    CharSet(Vec<SetItem>, bool),
+}
+
+/// Item of a character set `[...]`.
+#[derive(Debug, PartialEq)]
+enum SetItem {
+    Char(char),
+    Range(char, char),
+    Class(String),
+}
+
+/// Parses a character set. `chars[start]` is the character after the `[`.
+/// Returns the GlobElement and the index after the closing `]` or `None` if
+/// the set is not terminated.
+fn parse_char_set(chars: &[char], start: usize) -> Option<(GlobElement, usize)> {
+    let mut idx = start;
+    let mut negated = false;
+    let mut items = vec![];
+
+    if idx < chars.len() && (chars[idx] == '!' || chars[idx] == '^') {
+        negated = true;
+        idx += 1;
+    }
+
+    let mut first = true;
+    while idx < chars.len() {
+        let c = chars[idx];
+        if c == ']' && !first {
+            return Some((GlobElement::CharSet(items, negated), idx + 1));
+        }
+        first = false;
+
+        if c == '[' && idx + 1 < chars.len() && chars[idx + 1] == ':' {
+            // character class like [:alpha:]
+            let rest: String = chars[idx + 2..].iter().collect();
+            if let Some(end) = rest.find(":]") {
+                let class = rest[..end].to_string();
+                idx += 2 + class.chars().count() + 2;
+                items.push(SetItem::Class(class));
+                continue;
+            }
+        }
+
+        let c = if c == '\\' && idx + 1 < chars.len() {
+            idx += 1;
+            chars[idx]
+        } else {
+            c
+        };
+
+        if idx + 2 < chars.len() && chars[idx + 1] == '-' && chars[idx + 2] != ']' {
+            let mut end_idx = idx + 2;
+            if chars[end_idx] == '\\' && end_idx + 1 < chars.len() {
+                end_idx += 1;
+            }
+            items.push(SetItem::Range(c, chars[end_idx]));
+            idx = end_idx + 1;
+        } else {
+            items.push(SetItem::Char(c));
+            idx += 1;
+        }
+    }
+
+    None
+}
+
+/// Parses a glob pattern into a vector of GlobElements.
+fn parse_pattern(pattern: &str) -> Vec<GlobElement> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut elements = vec![];
+
+    let mut idx = 0;
+    while idx < chars.len() {
+        match chars[idx] {
+            '*' => {
+                // multiple stars are the same as one
+                if elements.last() != Some(&GlobElement::AnyString) {
+                    elements.push(GlobElement::AnyString);
+                }
+                idx += 1;
+            }
+            '?' => {
+                elements.push(GlobElement::AnyChar);
+                idx += 1;
+            }
+            '[' => match parse_char_set(&chars, idx + 1) {
+                Some((set, next_idx)) => {
+                    elements.push(set);
+                    idx = next_idx;
+                }
+                None => {
+                    elements.push(GlobElement::Char('['));
+                    idx += 1;
+                }
+            },
+            '\\' if idx + 1 < chars.len() => {
+                elements.push(GlobElement::Char(chars[idx + 1]));
+                idx += 2;
+            }
+            c => {
+                elements.push(GlobElement::Char(c));
+                idx += 1;
+            }
+        }
+    }
+
+    elements
+}
+
+/// Returns whether the character belongs to the named character class.
+/// Unknown classes never match.
+fn match_class(class: &str, c: char) -> bool {
+    match class {
+        "alnum" => c.is_alphanumeric(),
+        "alpha" => c.is_alphabetic(),
+        "blank" => c == ' ' || c == '\t',
+        "cntrl" => c.is_control(),
+        "digit" => c.is_ascii_digit(),
+        "graph" => !c.is_whitespace() && !c.is_control(),
+        "lower" => c.is_lowercase(),
+        "print" => !c.is_control(),
+        "punct" => c.is_ascii_punctuation(),
+        "space" => c.is_whitespace(),
+        "upper" => c.is_uppercase(),
+        "xdigit" => c.is_ascii_hexdigit(),
+        _ => false,
+    }
+}
+
+/// Compares two characters, optionally case-insensitive.
+fn char_eq(a: char, b: char, ignore_case: bool) -> bool {
+    if ignore_case {
+        a.to_lowercase().eq(b.to_lowercase())
+    } else {
+        a == b
+    }
+}
+
+/// Returns whether the single character is matched by the glob element.
+/// Must not be called with `GlobElement::AnyString`.
+fn match_element(element: &GlobElement, c: char, ignore_case: bool) -> bool {
+    match element {
+        GlobElement::Char(p) => char_eq(*p, c, ignore_case),
+        GlobElement::AnyChar => true,
+        GlobElement::AnyString => panic!("match_element: AnyString not supported"),
+        GlobElement::CharSet(items, negated) => {
+            let found = items.iter().any(|item| match item {
+                SetItem::Char(p) => char_eq(*p, c, ignore_case),
+                SetItem::Range(from, to) => {
+                    (*from..=*to).contains(&c)
+                        || (ignore_case
+                            && c.to_lowercase()
+                                .chain(c.to_uppercase())
+                                .any(|cc| (*from..=*to).contains(&cc)))
+                }
+                SetItem::Class(class) => match_class(class, c),
+            });
+            found != *negated
+        }
+    }
+}
+
+/// Returns whether the whole `value` is matched by the glob `pattern`.
+pub fn glob_match(pattern: &str, value: &str, ignore_case: bool) -> bool {
+    let elements = parse_pattern(pattern);
+    let chars: Vec<char> = value.chars().collect();
+
+    let mut p_idx = 0;
+    let mut v_idx = 0;
+    // Position after the last `*` and the value position it was tried with.
+    // Used for backtracking.
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while v_idx < chars.len() {
+        if p_idx < elements.len() {
+            if elements[p_idx] == GlobElement::AnyString {
+                p_idx += 1;
+                backtrack = Some((p_idx, v_idx));
+                continue;
+            }
+            if match_element(&elements[p_idx], chars[v_idx], ignore_case) {
+                p_idx += 1;
+                v_idx += 1;
+                continue;
+            }
+        }
+        // mismatch: let the last `*` consume one more character
+        match backtrack {
+            Some((star_p_idx, star_v_idx)) => {
+                p_idx = star_p_idx;
+                v_idx = star_v_idx + 1;
+                backtrack = Some((star_p_idx, v_idx));
+            }
+            None => return false,
+        }
+    }
+
+    // only trailing `*` may be left
+    elements[p_idx..]
+        .iter()
+        .all(|e| *e == GlobElement::AnyString)
+}
+
+#[cfg(test)]
+mod glob_tests {
+    use super::glob_match;
+
+    #[test]
+    fn test_literal() {
+        assert!(glob_match("abc", "abc", false));
+        assert!(!glob_match("abc", "abcd", false));
+        assert!(!glob_match("abc", "ab", false));
+        assert!(!glob_match("abc", "ABC", false));
+        assert!(glob_match("abc", "ABC", true));
+        assert!(glob_match("", "", false));
+        assert!(!glob_match("", "a", false));
+    }
+
+    #[test]
+    fn test_star() {
+        assert!(glob_match("*.csv", "input.csv", false));
+        assert!(glob_match("*.csv", ".csv", false));
+        assert!(glob_match("*.csv", "dir/input.csv", false));
+        assert!(!glob_match("*.csv", "input.csv.bak", false));
+        assert!(glob_match("*.CSV", "input.csv", true));
+        assert!(glob_match("a*b*c", "aXXbYYc", false));
+        assert!(glob_match("a*b*c", "abbbc", false));
+        assert!(!glob_match("a*b*c", "abbbcd", false));
+        assert!(glob_match("**", "", false));
+        assert!(glob_match("*", "anything", false));
+    }
+
+    #[test]
+    fn test_question_mark() {
+        assert!(glob_match("v?.?", "v1.2", false));
+        assert!(!glob_match("v?.?", "v1.23", false));
+        assert!(!glob_match("?", "", false));
+        assert!(glob_match("?", "ä", false));
+    }
+
+    #[test]
+    fn test_char_set() {
+        assert!(glob_match("[abc]x", "bx", false));
+        assert!(!glob_match("[abc]x", "dx", false));
+        assert!(glob_match("[a-z][0-9]", "k7", false));
+        assert!(!glob_match("[a-z][0-9]", "K7", false));
+        assert!(glob_match("[a-z][0-9]", "K7", true));
+        assert!(glob_match("[!a-z]", "K", false));
+        assert!(glob_match("[^a-z]", "K", false));
+        assert!(!glob_match("[!a-z]", "k", false));
+        assert!(glob_match("[]]", "]", false));
+        assert!(glob_match("[!]]", "a", false));
+        assert!(glob_match("[a-]", "-", false));
+        assert!(glob_match("[[:digit:]][[:alpha:]]", "1a", false));
+        assert!(!glob_match("[[:digit:]]", "a", false));
+        assert!(glob_match("[[:upper:]_]", "_", false));
+    }
+
+    #[test]
+    fn test_escape_and_unterminated() {
+        assert!(glob_match("\\*", "*", false));
+        assert!(!glob_match("\\*", "a", false));
+        assert!(glob_match("a\\?", "a?", false));
+        assert!(glob_match("[abc", "[abc", false));
+        assert!(glob_match("[\\]]", "]", false));
+    }
+}
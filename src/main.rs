@@ -6,21 +6,25 @@
 //
 
 mod cmd_line;
+mod glob;
 mod opt_def;
+mod response_file;
 mod shell_code;
 
 use crate::shell_code::VarValue;
 use shell_code::CodeChunk;
 use std::cell::Cell;
 use std::collections::HashMap;
+use std::env::VarError;
 use std::ffi::OsString;
-use std::io::{stdout, IsTerminal};
+use std::io::{stdin, stdout, IsTerminal, Read};
 use std::panic::catch_unwind;
 use std::process::exit;
 
-use crate::cmd_line::{CmdLineElement, CmdLineTokenizer};
-use crate::opt_def::{OptConfig, OptTarget, OptType};
+use crate::cmd_line::{CmdLineElement, CmdLineTokenizer, SingleDashMode};
+use crate::opt_def::{ArgConfig, OptConfig, OptTarget, OptType, ValueRestriction};
 use clap::{CommandFactory, Parser};
+use regex_lite::RegexBuilder;
 
 const PARSEARGS: &str = env!("CARGO_PKG_NAME");
 const GIT_HASH: &str = env!("GIT_HASH_STATUS");
@@ -57,6 +61,38 @@ struct CmdLineArgs {
     #[arg(short = 'a', long = "arg-callback", value_name = "SHELL-FUNC", value_parser = parse_shell_name, verbatim_doc_comment)]
     arg_callback: Option<String>,
 
+    /// Assign the program arguments to the named variables.
+    /// Like 'src,dst', 'input?' or 'files...'.
+    #[arg(
+        long = "args",
+        value_name = "ARG-DEFs",
+        conflicts_with = "arg_callback",
+        verbatim_doc_comment
+    )]
+    args: Option<String>,
+
+    /// Minimum number of program arguments.
+    /// Arguments collected with --remainder are not counted.
+    #[arg(long = "min-args", value_name = "N", verbatim_doc_comment)]
+    min_args: Option<usize>,
+
+    /// Maximum number of program arguments.
+    /// Arguments collected with --remainder are not counted.
+    #[arg(long = "max-args", value_name = "N", verbatim_doc_comment)]
+    max_args: Option<usize>,
+
+    /// Don't accept program arguments. Same as '--max-args 0'.
+    #[arg(long = "no-args", conflicts_with_all = ["min_args", "max_args"])]
+    no_args: bool,
+
+    /// Minimum number of arguments collected with --remainder.
+    #[arg(long = "min-remainder", value_name = "N", requires = "remainder")]
+    min_remainder: Option<usize>,
+
+    /// Maximum number of arguments collected with --remainder.
+    #[arg(long = "max-remainder", value_name = "N", requires = "remainder")]
+    max_remainder: Option<usize>,
+
     /// On error call this function before exiting the calling script.
     #[arg(short = 'e', long = "error-callback", value_name = "SHELL-FUNC", value_parser = parse_shell_name)]
     error_callback: Option<String>,
@@ -66,15 +102,75 @@ struct CmdLineArgs {
     #[arg(short = 'r', long = "remainder", value_name = "SHELL-VAR", value_parser = parse_shell_name, verbatim_doc_comment)]
     remainder: Option<String>,
 
+    /// Define a subcommand with its own options. Can be given multiple times.
+    /// The first none-option selects the subcommand.
+    #[arg(
+        long = "subcommand",
+        value_name = "NAME:OPT-DEFs",
+        verbatim_doc_comment
+    )]
+    subcommands: Option<Vec<String>>,
+
+    /// Variable to store the selected subcommand.
+    #[arg(long = "subcommand-var", value_name = "SHELL-VAR", default_value = "subcommand", value_parser = parse_shell_name)]
+    subcommand_var: String,
+
+    /// Handling of unknown options. One of:
+    /// error         - exit with error (default)
+    /// args          - keep them as program arguments
+    /// collect:ARRAY - collect them in the named array
+    #[arg(long = "unknown", value_name = "POLICY", default_value = "error", value_parser = parse_unknown_policy, verbatim_doc_comment)]
+    unknown: UnknownPolicy,
+
+    /// Accept unambiguous prefixes of long options. Like '--verb' for '--verbose'.
+    #[arg(long = "abbrev")]
+    abbrev: bool,
+
+    /// Handling of options like '-name'. One of:
+    /// short - cluster of short options '-n -a -m -e' (default)
+    /// long  - the long option 'name'
+    /// mixed - the long option 'name', if it exists, else short options
+    #[arg(long = "single-dash", value_name = "MODE", default_value = "short", value_parser = parse_single_dash_mode, verbatim_doc_comment)]
+    single_dash: SingleDashMode,
+
+    /// Support options with a leading '+' to turn off flags. Like '+x' for '-x'.
+    #[arg(long = "plus-options")]
+    plus_options: bool,
+
+    /// Match long options case-insensitive and accept the values of name
+    /// lists ('=L=...') in any case.
+    #[arg(long = "ignore-case")]
+    ignore_case: bool,
+
+    /// Replace script arguments '@FILE' with the words read from FILE.
+    #[arg(long = "response-files")]
+    response_files: bool,
+
+    /// Read the script arguments NUL-delimited from STDIN instead of
+    /// the command line. Like:
+    /// for a in "$@"; do printf '%s\0' "$a"; done | parseargs ...
+    #[arg(
+        long = "args-from-stdin0",
+        conflicts_with = "script_args",
+        verbatim_doc_comment
+    )]
+    args_from_stdin0: bool,
+
     /// Stop option processing on first none-option.
     #[arg(short = 'p', long = "posix")]
     posix: bool,
 
     /// Initialize all variables with '', except for counting variables,
-    /// as they are always initialized with 0.
+    /// as they are always initialized with 0, and variables with a
+    /// default value.
     #[arg(short = 'i', long = "init-vars")]
     init_vars: bool,
 
+    /// Options not given on the command line get their value from the
+    /// environment variable PREFIX + long option (upper case, '-' as '_').
+    #[arg(long = "env-prefix", value_name = "PREFIX", value_parser = parse_shell_name, verbatim_doc_comment)]
+    env_prefix: Option<String>,
+
     /// Enable support for --help as script option.
     #[arg(short = 'h', long = "help-opt", verbatim_doc_comment)]
     help_opt: bool,
@@ -105,6 +201,35 @@ struct CmdLineArgs {
     script_args: Vec<OsString>,
 }
 
+/// How to handle unknown options.
+#[derive(Clone, Debug, PartialEq)]
+enum UnknownPolicy {
+    /// Exit with an error message
+    Error,
+    /// Keep the unknown option as program argument
+    Args,
+    /// Collect the unknown options in the named array
+    Collect(String),
+}
+
+/// Used by Clap to parse the handling of options like `-name`.
+fn parse_single_dash_mode(arg: &str) -> Result<SingleDashMode, String> {
+    match arg {
+        "short" => Ok(SingleDashMode::Short),
+        "long" => Ok(SingleDashMode::Long),
+        "mixed" => Ok(SingleDashMode::Mixed),
+        _ => Err("Expected 'short', 'long' or 'mixed'".to_string()),
+    }
+}
+
+/// A subcommand with its own option definitions.
+struct Subcommand {
+    /// Name of the subcommand as given on the command line
+    name: String,
+    /// Options supported after the subcommand
+    opt_cfg_list: Vec<OptConfig>,
+}
+
 /// Exit after printing an error message.
 fn die_internal(msg: String) -> ! {
     eprintln!("{}: {}", PARSEARGS, msg);
@@ -126,10 +251,23 @@ fn parse_shell_name(arg: &str) -> Result<String, String> {
     Ok(arg.to_string())
 }
 
+/// Used by Clap to parse the policy for unknown options.
+fn parse_unknown_policy(arg: &str) -> Result<UnknownPolicy, String> {
+    match arg {
+        "error" => Ok(UnknownPolicy::Error),
+        "args" => Ok(UnknownPolicy::Args),
+        _ => match arg.strip_prefix("collect:") {
+            Some(array) => Ok(UnknownPolicy::Collect(parse_shell_name(array)?)),
+            None => Err("Expected 'error', 'args' or 'collect:ARRAY'".to_string()),
+        },
+    }
+}
+
 /// Produces the initial shell code. Like checking that required functions really exist and
 /// typesetting the variables (if supported by shell).
 fn shell_init_code(
-    opt_cfg_list: &Vec<OptConfig>,
+    opt_cfg_list: &[OptConfig],
+    arg_cfg_list: &[ArgConfig],
     cmd_line_args: &CmdLineArgs,
     init_vars: bool,
 ) -> Vec<CodeChunk> {
@@ -143,6 +281,37 @@ fn shell_init_code(
         init_code.push(CodeChunk::CheckForFunction(func.clone()));
     }
 
+    init_code.append(&mut options_init_code(opt_cfg_list, init_vars));
+
+    for arg_cfg in arg_cfg_list {
+        if arg_cfg.variadic {
+            init_code.push(CodeChunk::DeclareArrayVar(arg_cfg.name.clone()));
+            init_code.push(CodeChunk::AssignEmptyArray(arg_cfg.name.clone()));
+        } else if init_vars {
+            init_code.push(CodeChunk::AssignVar(
+                arg_cfg.name.clone(),
+                VarValue::StringValue("".to_string()),
+            ));
+        }
+    }
+
+    if let Some(array) = &cmd_line_args.remainder {
+        init_code.push(CodeChunk::DeclareArrayVar(array.clone()));
+        init_code.push(CodeChunk::AssignEmptyArray(array.clone()));
+    }
+    if let UnknownPolicy::Collect(array) = &cmd_line_args.unknown {
+        init_code.push(CodeChunk::DeclareArrayVar(array.clone()));
+        init_code.push(CodeChunk::AssignEmptyArray(array.clone()));
+    }
+
+    init_code
+}
+
+/// Produces the initial shell code for the given options. Checks that the
+/// functions exist and initializes the variables.
+fn options_init_code(opt_cfg_list: &[OptConfig], init_vars: bool) -> Vec<CodeChunk> {
+    let mut init_code: Vec<CodeChunk> = vec![];
+
     // Iterating opt_cfg_list multiple time, but I want a certain order of
     // the generated code.
 
@@ -162,19 +331,37 @@ fn shell_init_code(
     for opt_cfg in opt_cfg_list {
         let name = opt_cfg.get_target_name();
 
-        if opt_cfg.is_target_variable() {
+        if opt_cfg.is_target_array() {
+            // arrays are always initialized
+            init_code.push(CodeChunk::DeclareArrayVar(name.clone()));
+            init_code.push(CodeChunk::AssignEmptyArray(name.clone()));
+        } else if opt_cfg.is_target_map() {
+            // associative arrays are always initialized
+            init_code.push(CodeChunk::DeclareMapVar(name.clone()));
+            init_code.push(CodeChunk::AssignEmptyMap(name.clone()));
+        } else if opt_cfg.is_target_variable() {
+            if !handled_vars.contains(&name) {
+                if let Some(value) = get_default_value(opt_cfg_list, &name) {
+                    // default value is assigned independent of init_vars
+                    init_code.push(CodeChunk::AssignVar(name.clone(), value));
+                    handled_vars.push(name.clone());
+                    continue;
+                }
+            }
             if init_vars && !handled_vars.contains(&name) {
                 match &opt_cfg.opt_type {
-                    OptType::Flag(_) | OptType::Assignment(_) | OptType::ModeSwitch(_, _) => {
-                        init_code.push(CodeChunk::AssignVar(
-                            name.clone(),
-                            VarValue::StringValue("".to_string()),
-                        ))
-                    }
+                    OptType::Flag(_)
+                    | OptType::Assignment(_)
+                    | OptType::OptionalAssignment(_, _)
+                    | OptType::SubOptions(_, _)
+                    | OptType::ModeSwitch(_, _) => init_code.push(CodeChunk::AssignVar(
+                        name.clone(),
+                        VarValue::StringValue("".to_string()),
+                    )),
                     OptType::Counter(_) => {
                         init_code.push(CodeChunk::AssignVar(name.clone(), VarValue::IntValue(0)));
                     }
-                    OptType::Help(_) => {}
+                    OptType::Help(_) | OptType::Forward(_) | OptType::Map(_) => {}
                 }
                 handled_vars.push(name.clone());
             } else if let OptType::Counter(_) = &opt_cfg.opt_type {
@@ -183,14 +370,30 @@ fn shell_init_code(
         }
     }
 
-    if let Some(array) = &cmd_line_args.remainder {
-        init_code.push(CodeChunk::DeclareArrayVar(array.clone()));
-        init_code.push(CodeChunk::AssignEmptyArray(array.clone()));
+    // sub-options are initialized like options
+    for sub_cfg_list in opt_cfg_list.iter().filter_map(|oc| oc.get_sub_options()) {
+        init_code.append(&mut options_init_code(sub_cfg_list, init_vars));
     }
 
     init_code
 }
 
+/// Returns the default value for the named variable, if one of the option
+/// definitions for it defines a default value.
+///
+/// Exits if the default value does not match the value restriction of the option.
+fn get_default_value(opt_cfg_list: &[OptConfig], name: &String) -> Option<VarValue> {
+    let opt_cfg = opt_cfg_list
+        .iter()
+        .find(|oc| oc.default_value.is_some() && oc.get_target_name() == *name)?;
+
+    let value = opt_cfg.default_value.clone().unwrap();
+    match check_option_value(opt_cfg, value) {
+        Ok(value) => Some(value),
+        Err(msg) => die_internal(format!("Default value rejected: {}", msg)),
+    }
+}
+
 /// Optional String to bool.
 ///
 /// The values "true" and "yes" result in `true`.
@@ -226,39 +429,180 @@ fn optional_string_to_optional_u16(value: Option<&String>) -> Result<Option<u16>
     }
 }
 
+/// Check the value of an assignment option against the value restriction of
+/// the option (if any).
+///
+/// Returns the value to assign or Err if the value is not accepted.
+fn check_option_value(oc: &OptConfig, value: String) -> Result<VarValue, String> {
+    check_value(&oc.value_restriction, &oc.options_string(), value)
+}
+
+/// Check a value against the given value restriction (if any).
+/// `what` names the option or argument in error messages.
+///
+/// Returns the value to assign or Err if the value is not accepted.
+fn check_value(
+    value_restriction: &Option<ValueRestriction>,
+    what: &str,
+    value: String,
+) -> Result<VarValue, String> {
+    match value_restriction {
+        None => Ok(VarValue::StringValue(value)),
+        Some(ValueRestriction::NameSet(names, ignore_case)) => {
+            let found = names.iter().find(|name| {
+                if *ignore_case {
+                    name.to_lowercase() == value.to_lowercase()
+                } else {
+                    **name == value
+                }
+            });
+            match found {
+                // assign the name as given in the option definition
+                Some(name) => Ok(VarValue::StringValue(name.clone())),
+                None => Err(format!(
+                    "Invalid value for {}: '{}' (allowed: {})",
+                    what,
+                    value,
+                    names.join(", ")
+                )),
+            }
+        }
+        Some(ValueRestriction::RegEx(pattern, ignore_case)) => {
+            // The whole value has to match, so anchor the pattern.
+            // The pattern was already validated while parsing the option definition.
+            let regex = RegexBuilder::new(&format!("^(?:{})$", pattern))
+                .case_insensitive(*ignore_case)
+                .build()
+                .unwrap();
+            if regex.is_match(&value) {
+                Ok(VarValue::StringValue(value))
+            } else {
+                Err(format!(
+                    "Invalid value for {}: '{}' (must match regex '{}')",
+                    what, value, pattern
+                ))
+            }
+        }
+        Some(ValueRestriction::Integer(min, max)) => match value.parse::<i64>() {
+            Ok(int) => {
+                check_range(what, &value, int, min, max)?;
+                Ok(VarValue::IntValue(int))
+            }
+            Err(_) => Err(format!("Invalid integer value for {}: '{}'", what, value)),
+        },
+        Some(ValueRestriction::Unsigned(min, max)) => match value.parse::<u64>() {
+            Ok(uint) if uint <= i64::MAX as u64 => {
+                check_range(what, &value, uint, min, max)?;
+                Ok(VarValue::IntValue(uint as i64))
+            }
+            _ => Err(format!(
+                "Invalid unsigned integer value for {}: '{}'",
+                what, value
+            )),
+        },
+        Some(ValueRestriction::Float(min, max)) => match value.parse::<f64>() {
+            Ok(float) if float.is_finite() => {
+                check_range(what, &value, float, min, max)?;
+                // Shells don't support floating point values, so the value is
+                // assigned as given.
+                Ok(VarValue::StringValue(value))
+            }
+            _ => Err(format!("Invalid float value for {}: '{}'", what, value)),
+        },
+        Some(ValueRestriction::Glob(pattern, ignore_case)) => {
+            if glob::glob_match(pattern, &value, *ignore_case) {
+                Ok(VarValue::StringValue(value))
+            } else {
+                Err(format!(
+                    "Invalid value for {}: '{}' (must match pattern '{}')",
+                    what, value, pattern
+                ))
+            }
+        }
+    }
+}
+
+/// Check whether a numeric value is in the (optionally open) range `min` to `max`.
+/// Both bounds are inclusive.
+fn check_range<T: PartialOrd + std::fmt::Display>(
+    what: &str,
+    value: &str,
+    number: T,
+    min: &Option<T>,
+    max: &Option<T>,
+) -> Result<(), String> {
+    let below = matches!(min, Some(m) if number < *m);
+    let above = matches!(max, Some(m) if number > *m);
+    if below || above {
+        let allowed = match (min, max) {
+            (Some(min), Some(max)) => format!("{} - {}", min, max),
+            (Some(min), None) => format!(">= {}", min),
+            (None, Some(max)) => format!("<= {}", max),
+            (None, None) => String::new(),
+        };
+        Err(format!(
+            "Value out of range for {}: '{}' (allowed: {})",
+            what, value, allowed
+        ))
+    } else {
+        Ok(())
+    }
+}
+
 /// Assign a value to an option target.
-/// Return either aCodeChunk for a variable assignment or a function call.
+/// Return either a CodeChunk for a variable assignment, a function call or
+/// adding to an array.
 fn assign_target(target: &OptTarget, value: VarValue) -> CodeChunk {
     match target {
         OptTarget::Variable(name) => CodeChunk::AssignVar(name.clone(), value),
         OptTarget::Function(name) => CodeChunk::CallFunction(name.clone(), value),
+        OptTarget::Array(name) => CodeChunk::AddToArray(name.clone(), value),
     }
 }
 
+/// Splits the input read with `--args-from-stdin0` into the script arguments.
+/// Every argument is terminated by a NUL byte, the terminator of the last
+/// argument is optional.
+fn split_nul_delimited(input: Vec<u8>) -> Result<Vec<String>, String> {
+    let mut args = vec![];
+    if input.is_empty() {
+        return Ok(args);
+    }
+    let input = input.strip_suffix(b"\0").unwrap_or(&input);
+    for bytes in input.split(|b| *b == 0) {
+        match String::from_utf8(bytes.to_vec()) {
+            Ok(utf8) => args.push(utf8),
+            Err(_) => Err(format!(
+                "Invalid UTF-8 char(s) in {:?}",
+                String::from_utf8_lossy(bytes)
+            ))?,
+        }
+    }
+    Ok(args)
+}
+
 /// Parses the shell arguments based on the given option definition.
-/// Returns a vector of CodeChunks.
+/// If subcommands are given, the first non-option selects the subcommand
+/// and the following options are parsed with its option definitions.
+///
+/// Returns a vector of CodeChunks and the index of the selected subcommand.
 fn parse_shell_options(
     opt_cfg_list: &mut Vec<OptConfig>,
+    subcommands: &[Subcommand],
+    arg_cfg_list: &[ArgConfig],
     cmd_line_args: &CmdLineArgs,
-) -> Result<Vec<CodeChunk>, String> {
+) -> Result<(Vec<CodeChunk>, Option<usize>), String> {
     let mut shell_code: Vec<CodeChunk> = vec![];
     let mut arguments: Vec<String> = vec![];
 
-    // Lookup table from target name to position in vector.
-    // Needed for duplication checks of Mode-Switches.
-    let mut shell_name_table: HashMap<String, Vec<usize>> = HashMap::new();
-
-    for (pos, e) in opt_cfg_list.iter().enumerate() {
-        let name = &e.get_target_name();
-
-        if shell_name_table.contains_key(name) {
-            shell_name_table.get_mut(name).unwrap().push(pos);
-        } else {
-            shell_name_table.insert(name.clone(), vec![pos]);
+    let mut script_args = vec![];
+    if cmd_line_args.args_from_stdin0 {
+        let mut input = vec![];
+        if let Err(e) = stdin().read_to_end(&mut input) {
+            Err(format!("Can't read script arguments from STDIN: {}", e))?
         }
+        script_args = split_nul_delimited(input)?;
     }
-
-    let mut script_args = vec![];
     for oss in &cmd_line_args.script_args {
         let result = OsString::into_string(oss.clone());
         if let Ok(utf8) = result {
@@ -270,12 +614,34 @@ fn parse_shell_options(
             ))?
         }
     }
+    if cmd_line_args.response_files {
+        script_args = response_file::expand_response_files(script_args)?;
+    }
 
-    let mut cl_tok = CmdLineTokenizer::new(script_args, cmd_line_args.posix);
+    // With subcommands, the global options end on the first non-option.
+    let mut cl_tok =
+        CmdLineTokenizer::new(script_args, cmd_line_args.posix || !subcommands.is_empty());
+    cl_tok.set_single_dash(
+        cmd_line_args.single_dash,
+        long_option_names(&opt_cfg_list.iter().collect::<Vec<_>>()),
+    );
+    cl_tok.set_plus_options(cmd_line_args.plus_options);
+    cl_tok.set_ignore_case(cmd_line_args.ignore_case);
+    cl_tok.set_numeric_options(opt_cfg_list.iter().any(|oc| oc.numeric));
 
     let mut after_separator = false;
     let mut prev_counter: Option<(&OptTarget, u16)> = None;
 
+    // the options currently supported. Switched to the options of the subcommand
+    // when it is found.
+    let mut active_cfg_list: &[OptConfig] = opt_cfg_list;
+    let mut subcommand_idx: Option<usize> = None;
+
+    // number of program arguments (without the remainder)
+    let mut arg_count: usize = 0;
+    // number of arguments collected with --remainder
+    let mut remainder_count: usize = 0;
+
     while let Some(e) = cl_tok.next() {
         if let CmdLineElement::Separator = e {
             prev_counter = counter_assign(&mut shell_code, prev_counter);
@@ -283,42 +649,182 @@ fn parse_shell_options(
             continue;
         } else if let CmdLineElement::Argument(value) = e {
             prev_counter = counter_assign(&mut shell_code, prev_counter);
-            if let (true, Some(array)) = (after_separator, &cmd_line_args.remainder) {
+            if !subcommands.is_empty() && subcommand_idx.is_none() && !after_separator {
+                let idx = match subcommands.iter().position(|sc| sc.name == value) {
+                    Some(idx) => idx,
+                    None => return Err(format!("Unknown subcommand: {}", value)),
+                };
+                shell_code.push(CodeChunk::AssignVar(
+                    cmd_line_args.subcommand_var.clone(),
+                    VarValue::StringValue(value),
+                ));
+                active_cfg_list = &subcommands[idx].opt_cfg_list;
+                subcommand_idx = Some(idx);
+                cl_tok.resume_options(cmd_line_args.posix);
+                // `--help` and `--version` from the global options are still supported
+                let sub_cfg_list: Vec<&OptConfig> = active_cfg_list
+                    .iter()
+                    .chain(
+                        opt_cfg_list
+                            .iter()
+                            .filter(|oc| matches!(oc.opt_type, OptType::Help(_))),
+                    )
+                    .collect();
+                cl_tok.set_single_dash(cmd_line_args.single_dash, long_option_names(&sub_cfg_list));
+                cl_tok.set_numeric_options(active_cfg_list.iter().any(|oc| oc.numeric));
+            } else if let (true, Some(array)) = (after_separator, &cmd_line_args.remainder) {
+                remainder_count += 1;
                 shell_code.push(CodeChunk::AddToArray(
                     array.clone(),
                     VarValue::StringValue(value),
                 ));
             } else if let Some(func) = &cmd_line_args.arg_callback {
+                arg_count += 1;
                 shell_code.push(CodeChunk::CallFunction(
                     func.clone(),
                     VarValue::StringValue(value),
                 ));
             } else {
+                arg_count += 1;
                 arguments.push(value);
             }
         } else {
+            let e = if cmd_line_args.abbrev || cmd_line_args.ignore_case {
+                // `--help` and `--version` from the global options are also candidates
+                let candidates: Vec<&OptConfig> = active_cfg_list
+                    .iter()
+                    .chain(opt_cfg_list.iter().filter(|oc| {
+                        subcommand_idx.is_some() && matches!(oc.opt_type, OptType::Help(_))
+                    }))
+                    .collect();
+                let e = if cmd_line_args.ignore_case {
+                    resolve_case(e, &candidates)
+                } else {
+                    e
+                };
+                if cmd_line_args.abbrev {
+                    resolve_abbreviation(e, &candidates, cmd_line_args.ignore_case)?
+                } else {
+                    e
+                }
+            } else {
+                e
+            };
+
             let opt_value = match &e {
                 CmdLineElement::LongOptionValue(_, v) => Some(v),
+                // the number is the value of the numeric option
+                CmdLineElement::NumericOption(n) => Some(n),
                 _ => None,
             };
 
-            let opt_config = opt_cfg_list.iter().find(|cfg| cfg.match_option(&e));
+            // `--help` and `--version` are also supported after the subcommand
+            let opt_config = active_cfg_list
+                .iter()
+                .find(|cfg| cfg.match_option(&e))
+                .or_else(|| {
+                    opt_cfg_list.iter().find(|cfg| {
+                        matches!(cfg.opt_type, OptType::Help(_)) && cfg.match_option(&e)
+                    })
+                });
 
             if opt_config.is_none() {
-                return Err(format!("Unknown option: {}", e));
+                if cmd_line_args.unknown == UnknownPolicy::Error {
+                    // Report long options as given, as they might be given with a single dash.
+                    let given = match &e {
+                        CmdLineElement::LongOption(_) | CmdLineElement::LongOptionValue(_, _) => {
+                            cl_tok
+                                .current_part()
+                                .map(|part| match part.split_once('=') {
+                                    Some((name, _)) => name.to_string(),
+                                    None => part,
+                                })
+                        }
+                        _ => None,
+                    };
+                    return Err(format!(
+                        "Unknown option: {}",
+                        given.unwrap_or_else(|| e.to_string())
+                    ));
+                }
+                prev_counter = counter_assign(&mut shell_code, prev_counter);
+                // Forward the unknown option verbatim. For combined short options
+                // the remaining characters are forwarded with it, as they might be
+                // its option-argument. Long options might be given with a single dash.
+                let unknown = match &e {
+                    CmdLineElement::ShortOption(_) | CmdLineElement::PlusOption(_) => format!(
+                        "{}{}",
+                        e,
+                        cl_tok.get_attached_option_argument().unwrap_or_default()
+                    ),
+                    _ => cl_tok.current_part().unwrap_or_else(|| e.to_string()),
+                };
+                if let UnknownPolicy::Collect(array) = &cmd_line_args.unknown {
+                    shell_code.push(CodeChunk::AddToArray(
+                        array.clone(),
+                        VarValue::StringValue(unknown),
+                    ));
+                } else if let Some(func) = &cmd_line_args.arg_callback {
+                    arg_count += 1;
+                    shell_code.push(CodeChunk::CallFunction(
+                        func.clone(),
+                        VarValue::StringValue(unknown),
+                    ));
+                } else {
+                    arg_count += 1;
+                    arguments.push(unknown);
+                }
             } else if let Some(oc) = opt_config {
                 // Check duplicate options. Counter options and options that trigger a function call
                 // can be used multiple times.
-                if oc.assigned.get() && !oc.is_duplicate_allowed() {
+                // A flag might be toggled with plus and minus options, like `-x +x`.
+                let is_plus = matches!(e, CmdLineElement::PlusOption(_));
+                let toggled =
+                    matches!(oc.opt_type, OptType::Flag(_)) && oc.plus_given.get() != is_plus;
+                if oc.assigned.get() && !oc.is_duplicate_allowed() && !toggled {
                     return Err(format!("Duplicate option: {} ({})", e, oc.options_string()));
                 }
                 oc.assigned.set(true);
+                oc.plus_given.set(is_plus);
 
                 if oc.singleton {
                     shell_code.clear();
+                    // keep the selected subcommand
+                    if let Some(idx) = subcommand_idx {
+                        shell_code.push(CodeChunk::AssignVar(
+                            cmd_line_args.subcommand_var.clone(),
+                            VarValue::StringValue(subcommands[idx].name.clone()),
+                        ));
+                    }
                 }
 
                 match &oc.opt_type {
+                    // `--no-<long>`: set flag to false or clear the assigned value
+                    _ if oc.is_negated_option(&e) => {
+                        prev_counter = counter_assign(&mut shell_code, prev_counter);
+                        if opt_value.is_some() {
+                            Err(format!("{}: No value supported.", oc.options_string()))?;
+                        }
+                        shell_code.push(match (&oc.opt_type, oc.get_target()) {
+                            (_, OptTarget::Array(name)) => {
+                                CodeChunk::AssignEmptyArray(name.clone())
+                            }
+                            (OptType::Map(OptTarget::Variable(name)), _) => {
+                                CodeChunk::AssignEmptyMap(name.clone())
+                            }
+                            (OptType::Flag(target), _) => {
+                                assign_target(target, VarValue::BoolValue(false))
+                            }
+                            (_, target) => {
+                                assign_target(target, VarValue::StringValue(String::new()))
+                            }
+                        });
+                    }
+                    // `+x`: set flag to false
+                    OptType::Flag(target) if matches!(e, CmdLineElement::PlusOption(_)) => {
+                        prev_counter = counter_assign(&mut shell_code, prev_counter);
+                        shell_code.push(assign_target(target, VarValue::BoolValue(false)));
+                    }
                     OptType::Flag(target) => {
                         prev_counter = counter_assign(&mut shell_code, prev_counter);
                         let bool_val = VarValue::BoolValue(optional_str_to_bool(opt_value, true)?);
@@ -340,11 +846,27 @@ fn parse_shell_options(
                             None => cl_tok.get_option_argument(),
                         };
                         if let Some(opt_arg) = opt_arg {
-                            shell_code.push(assign_target(target, VarValue::StringValue(opt_arg)));
+                            for value in oc.split_value(opt_arg) {
+                                let value = check_option_value(oc, value)?;
+                                shell_code.push(assign_target(target, value));
+                            }
                         } else {
                             return Err(format!("Missing argument for: {}", e));
                         }
                     }
+                    OptType::OptionalAssignment(target, fallback) => {
+                        prev_counter = counter_assign(&mut shell_code, prev_counter);
+                        // Never consume the next command line part, the argument
+                        // has to be attached to the option.
+                        let opt_arg = match opt_value {
+                            Some(v) => Some(v.clone()),
+                            None => cl_tok.get_attached_option_argument(),
+                        };
+                        for value in oc.split_value(opt_arg.unwrap_or(fallback.clone())) {
+                            let value = check_option_value(oc, value)?;
+                            shell_code.push(assign_target(target, value));
+                        }
+                    }
                     OptType::Counter(target) => {
                         if let Some((prev_target, _)) = prev_counter {
                             if prev_target != target {
@@ -364,17 +886,219 @@ fn parse_shell_options(
                         }
                         shell_code.push(assign_target(target, VarValue::None));
                     }
+                    OptType::Map(target) => {
+                        prev_counter = counter_assign(&mut shell_code, prev_counter);
+                        let opt_arg = match opt_value {
+                            Some(v) => Some(v.clone()),
+                            None => cl_tok.get_option_argument(),
+                        };
+                        if let Some(opt_arg) = opt_arg {
+                            // split at the first '=', the key must not be empty
+                            let (key, value) = match opt_arg.split_once('=') {
+                                Some((key, value)) if !key.is_empty() => {
+                                    (key.to_string(), value.to_string())
+                                }
+                                _ => {
+                                    return Err(format!(
+                                        "Invalid value for {}: '{}' (expected KEY=VALUE)",
+                                        oc.options_string(),
+                                        opt_arg
+                                    ))
+                                }
+                            };
+                            shell_code.push(match target {
+                                OptTarget::Function(name) => {
+                                    CodeChunk::CallFunctionWithArgs(name.clone(), vec![key, value])
+                                }
+                                _ => CodeChunk::AddToMap(
+                                    oc.get_target_name(),
+                                    key,
+                                    VarValue::StringValue(value),
+                                ),
+                            });
+                        } else {
+                            return Err(format!("Missing argument for: {}", e));
+                        }
+                    }
+                    OptType::SubOptions(target, sub_cfg_list) => {
+                        prev_counter = counter_assign(&mut shell_code, prev_counter);
+                        let opt_arg = match opt_value {
+                            Some(v) => Some(v.clone()),
+                            None => cl_tok.get_option_argument(),
+                        };
+                        if let Some(opt_arg) = opt_arg {
+                            shell_code.push(assign_target(
+                                target,
+                                VarValue::StringValue(opt_arg.clone()),
+                            ));
+                            parse_sub_options(oc, sub_cfg_list, &opt_arg, &mut shell_code)?;
+                        } else {
+                            return Err(format!("Missing argument for: {}", e));
+                        }
+                    }
+                    OptType::Forward(target) => {
+                        prev_counter = counter_assign(&mut shell_code, prev_counter);
+                        // An option-argument is the first word to forward, followed
+                        // by the unprocessed rest of the command line.
+                        let mut words: Vec<String> = match opt_value {
+                            Some(v) => vec![v.clone()],
+                            None => cl_tok.get_attached_option_argument().into_iter().collect(),
+                        };
+                        words.append(&mut cl_tok.take_remaining());
+                        if words.is_empty() {
+                            return Err(format!("Missing argument for: {}", e));
+                        }
+                        if let OptTarget::Function(name) = target {
+                            shell_code.push(CodeChunk::CallFunctionWithArgs(name.clone(), words));
+                        } else {
+                            for word in words {
+                                shell_code.push(assign_target(target, VarValue::StringValue(word)));
+                            }
+                        }
+                    }
                 }
 
                 if oc.singleton {
                     shell_code.push(CodeChunk::Exit(0));
-                    return Ok(shell_code);
+                    return Ok((shell_code, subcommand_idx));
                 }
             }
         }
     }
     counter_assign(&mut shell_code, prev_counter);
 
+    if !subcommands.is_empty() && subcommand_idx.is_none() {
+        let names: Vec<&str> = subcommands.iter().map(|sc| sc.name.as_str()).collect();
+        return Err(format!("Missing subcommand (one of: {})", names.join(", ")));
+    }
+
+    // Options not given on the command line might get their value from the environment.
+    assign_env_vars(opt_cfg_list, &mut shell_code)?;
+    check_options_usage(opt_cfg_list)?;
+    if let Some(idx) = subcommand_idx {
+        assign_env_vars(&subcommands[idx].opt_cfg_list, &mut shell_code)?;
+        check_options_usage(&subcommands[idx].opt_cfg_list)?;
+    }
+
+    check_argument_count(arg_count, remainder_count, cmd_line_args)?;
+
+    if !arg_cfg_list.is_empty() {
+        assign_arguments(arg_cfg_list, arguments, &mut shell_code)?;
+        arguments = vec![];
+    }
+
+    shell_code.push(CodeChunk::SetArgs(arguments));
+
+    Ok((shell_code, subcommand_idx))
+}
+
+/// Checks the number of program arguments against `--min-args`, `--max-args`
+/// and `--no-args`.
+fn check_argument_count(
+    arg_count: usize,
+    remainder_count: usize,
+    cmd_line_args: &CmdLineArgs,
+) -> Result<(), String> {
+    if cmd_line_args.no_args && arg_count > 0 {
+        return Err("No arguments allowed".to_string());
+    }
+    check_count(
+        "arguments",
+        arg_count,
+        cmd_line_args.min_args,
+        cmd_line_args.max_args,
+    )?;
+    check_count(
+        "arguments after '--'",
+        remainder_count,
+        cmd_line_args.min_remainder,
+        cmd_line_args.max_remainder,
+    )
+}
+
+/// Checks that `count` is within the optional bounds `min` and `max`.
+/// `what` names the counted arguments in the error message.
+fn check_count(
+    what: &str,
+    count: usize,
+    min: Option<usize>,
+    max: Option<usize>,
+) -> Result<(), String> {
+    if let Some(min) = min {
+        if count < min {
+            return Err(format!(
+                "Too few {}: {} given, at least {} required",
+                what, count, min
+            ));
+        }
+    }
+    if let Some(max) = max {
+        if count > max {
+            return Err(format!(
+                "Too many {}: {} given, at most {} allowed",
+                what, count, max
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Assigns the program arguments to the variables of the argument definitions.
+/// A variadic argument collects the remaining arguments in an array.
+///
+/// Returns Err if an argument is missing, not accepted or unexpected.
+fn assign_arguments(
+    arg_cfg_list: &[ArgConfig],
+    arguments: Vec<String>,
+    shell_code: &mut Vec<CodeChunk>,
+) -> Result<(), String> {
+    let mut args = arguments.into_iter();
+    for ac in arg_cfg_list {
+        if ac.variadic {
+            let mut count = 0;
+            for value in args.by_ref() {
+                let value = check_value(&ac.value_restriction, &ac.display_name(), value)?;
+                shell_code.push(CodeChunk::AddToArray(ac.name.clone(), value));
+                count += 1;
+            }
+            if count == 0 && !ac.optional {
+                return Err(format!("Missing argument: {}", ac.display_name()));
+            }
+        } else {
+            match args.next() {
+                Some(value) => {
+                    let value = check_value(&ac.value_restriction, &ac.display_name(), value)?;
+                    shell_code.push(CodeChunk::AssignVar(ac.name.clone(), value));
+                }
+                None if ac.optional => (),
+                None => return Err(format!("Missing argument: {}", ac.display_name())),
+            }
+        }
+    }
+
+    match args.next() {
+        Some(value) => Err(format!("Unexpected argument: '{}'", value)),
+        None => Ok(()),
+    }
+}
+
+/// Checks the usage of the options after the command line was processed.
+/// Detects mutual exclusive ModeSwitches and missing required options.
+fn check_options_usage(opt_cfg_list: &[OptConfig]) -> Result<(), String> {
+    // Lookup table from target name to position in vector.
+    // Needed for duplication checks of Mode-Switches.
+    let mut shell_name_table: HashMap<String, Vec<usize>> = HashMap::new();
+
+    for (pos, e) in opt_cfg_list.iter().enumerate() {
+        let name = &e.get_target_name();
+
+        if shell_name_table.contains_key(name) {
+            shell_name_table.get_mut(name).unwrap().push(pos);
+        } else {
+            shell_name_table.insert(name.clone(), vec![pos]);
+        }
+    }
+
     // Check duplicates for ModeSwitches
     // and handle required
     for name in shell_name_table.keys() {
@@ -405,7 +1129,7 @@ fn parse_shell_options(
             }
         }
 
-        for oc in &mut *opt_cfg_list {
+        for oc in opt_cfg_list {
             match oc.opt_type {
                 OptType::ModeSwitch(_, _) => (),
                 _ => {
@@ -420,9 +1144,260 @@ fn parse_shell_options(
         }
     }
 
-    shell_code.push(CodeChunk::SetArgs(arguments));
+    Ok(())
+}
+
+/// Assigns the values of the environment variables of options that were not
+/// given on the command line.
+fn assign_env_vars(
+    opt_cfg_list: &[OptConfig],
+    shell_code: &mut Vec<CodeChunk>,
+) -> Result<(), String> {
+    for oc in opt_cfg_list {
+        if oc.assigned.get() {
+            continue;
+        }
+        if let Some(env_var) = &oc.env_var {
+            let value = match std::env::var(env_var) {
+                Ok(v) if !v.is_empty() => v,
+                Ok(_) | Err(VarError::NotPresent) => continue,
+                Err(VarError::NotUnicode(_)) => Err(format!(
+                    "Environment variable {} contains invalid UTF-8",
+                    env_var
+                ))?,
+            };
+            let from_env = |msg: String| format!("{} (from environment variable {})", msg, env_var);
+            let var_values = match &oc.opt_type {
+                OptType::Flag(_) => vec![VarValue::BoolValue(
+                    optional_str_to_bool(Some(&value), true).map_err(from_env)?,
+                )],
+                OptType::Counter(_) => {
+                    let count = optional_string_to_optional_u16(Some(&value)).map_err(from_env)?;
+                    vec![VarValue::IntValue(count.unwrap_or(0) as i64)]
+                }
+                _ => oc
+                    .split_value(value)
+                    .into_iter()
+                    .map(|v| check_option_value(oc, v).map_err(from_env))
+                    .collect::<Result<Vec<_>, String>>()?,
+            };
+            oc.assigned.set(true);
+            for var_value in var_values {
+                shell_code.push(assign_target(oc.get_target(), var_value));
+            }
+        }
+    }
+    Ok(())
+}
 
-    Ok(shell_code)
+/// Sets the environment variable of all options that support it and have a long option,
+/// but no explicit environment variable. The name is the prefix followed by the first
+/// long option in upper case. Characters that are not alphanumeric are replaced by `_`.
+fn apply_env_prefix(opt_cfg_list: &mut [OptConfig], prefix: &str) {
+    for oc in opt_cfg_list {
+        if oc.env_var.is_none() && oc.is_env_var_supported() {
+            if let Some(long) = oc.opt_strings.first() {
+                let name: String = long
+                    .chars()
+                    .map(|c| {
+                        if c.is_ascii_alphanumeric() {
+                            c.to_ascii_uppercase()
+                        } else {
+                            '_'
+                        }
+                    })
+                    .collect();
+                oc.env_var = Some(format!("{}{}", prefix, name));
+            }
+        }
+    }
+}
+
+/// Returns the long options (including negated ones) with the index of the
+/// option they belong to.
+fn indexed_long_options(opt_cfg_list: &[&OptConfig]) -> Vec<(usize, String)> {
+    let mut long_options: Vec<(usize, String)> = vec![];
+    for (idx, oc) in opt_cfg_list.iter().enumerate() {
+        for lng in &oc.opt_strings {
+            long_options.push((idx, lng.clone()));
+            if oc.negatable {
+                long_options.push((idx, format!("no-{}", lng)));
+            }
+        }
+    }
+    long_options
+}
+
+/// Returns the long options (including negated ones) of the given options.
+fn long_option_names(opt_cfg_list: &[&OptConfig]) -> Vec<String> {
+    indexed_long_options(opt_cfg_list)
+        .into_iter()
+        .map(|(_, lng)| lng)
+        .collect()
+}
+
+/// Resolves an abbreviated long option to the full long option.
+/// Negated long options (`--no-<long>`) are also considered.
+///
+/// Returns the element unchanged if it is not a long option, matches a long
+/// option exactly or doesn't match any long option.
+/// Returns Err if the abbreviation matches long options of multiple options.
+fn resolve_abbreviation(
+    e: CmdLineElement,
+    opt_cfg_list: &[&OptConfig],
+    ignore_case: bool,
+) -> Result<CmdLineElement, String> {
+    let name = match &e {
+        CmdLineElement::LongOption(name) | CmdLineElement::LongOptionValue(name, _) => name,
+        _ => return Ok(e),
+    };
+
+    let long_options = indexed_long_options(opt_cfg_list);
+
+    if long_options.iter().any(|(_, lng)| lng == name) {
+        return Ok(e);
+    }
+
+    let candidates: Vec<&(usize, String)> = long_options
+        .iter()
+        .filter(|(_, lng)| {
+            if ignore_case {
+                lng.to_lowercase().starts_with(&name.to_lowercase())
+            } else {
+                lng.starts_with(name.as_str())
+            }
+        })
+        .collect();
+
+    match candidates.first() {
+        None => Ok(e),
+        Some((first_idx, full_name)) => {
+            if candidates.iter().any(|(idx, _)| idx != first_idx) {
+                let names: Vec<String> = candidates
+                    .iter()
+                    .map(|(_, lng)| format!("--{}", lng))
+                    .collect();
+                return Err(format!(
+                    "Ambiguous option: --{} ({})",
+                    name,
+                    names.join(", ")
+                ));
+            }
+            Ok(with_long_name(e, full_name.clone()))
+        }
+    }
+}
+
+/// Resolves a long option given in a different case (like `--Verbose`) to
+/// the long option as defined (`--verbose`). Negated long options are also
+/// considered.
+///
+/// Returns the element unchanged if it is not a long option or doesn't
+/// match any long option.
+fn resolve_case(e: CmdLineElement, opt_cfg_list: &[&OptConfig]) -> CmdLineElement {
+    let name = match &e {
+        CmdLineElement::LongOption(name) | CmdLineElement::LongOptionValue(name, _) => {
+            name.to_lowercase()
+        }
+        _ => return e,
+    };
+
+    match long_option_names(opt_cfg_list)
+        .into_iter()
+        .find(|lng| lng.to_lowercase() == name)
+    {
+        Some(lng) => with_long_name(e, lng),
+        None => e,
+    }
+}
+
+/// Returns the long option element with the name replaced.
+fn with_long_name(e: CmdLineElement, name: String) -> CmdLineElement {
+    match e {
+        CmdLineElement::LongOptionValue(_, value) => CmdLineElement::LongOptionValue(name, value),
+        _ => CmdLineElement::LongOption(name),
+    }
+}
+
+/// Parses the option-argument of an option with sub-options. The
+/// option-argument is a comma-separated list of sub-options, that might have
+/// a value. Like `ro,uid=1000`.
+fn parse_sub_options(
+    oc: &OptConfig,
+    sub_cfg_list: &[OptConfig],
+    opt_arg: &str,
+    shell_code: &mut Vec<CodeChunk>,
+) -> Result<(), String> {
+    // Every occurrence of the option is checked on its own.
+    for sub_cfg in sub_cfg_list {
+        sub_cfg.assigned.set(false);
+    }
+
+    for sub_opt in opt_arg.split(',').filter(|s| !s.is_empty()) {
+        let (name, value) = match sub_opt.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (sub_opt, None),
+        };
+        let el = if name.chars().count() == 1 {
+            CmdLineElement::ShortOption(name.chars().next().unwrap())
+        } else {
+            CmdLineElement::LongOption(name.to_string())
+        };
+        let sub_cfg = match sub_cfg_list.iter().find(|cfg| cfg.match_option(&el)) {
+            Some(sub_cfg) => sub_cfg,
+            None => {
+                return Err(format!(
+                    "Unknown sub-option '{}' of {}",
+                    name,
+                    oc.options_string()
+                ))
+            }
+        };
+        let what = format!("sub-option '{}' of {}", name, oc.options_string());
+        if sub_cfg.assigned.get() && !sub_cfg.is_duplicate_allowed() {
+            return Err(format!("Duplicate {}", what));
+        }
+        sub_cfg.assigned.set(true);
+
+        match &sub_cfg.opt_type {
+            OptType::Flag(target) => {
+                let bool_val = match optional_str_to_bool(value.as_ref(), true) {
+                    Ok(b) => b,
+                    Err(_) => Err(format!(
+                        "Invalid boolean value for {}: '{}'",
+                        what,
+                        value.unwrap_or_default()
+                    ))?,
+                };
+                shell_code.push(assign_target(target, VarValue::BoolValue(bool_val)));
+            }
+            OptType::ModeSwitch(target, mode_value) => {
+                if value.is_some() {
+                    Err(format!("No value supported for {}", what))?;
+                }
+                shell_code.push(assign_target(
+                    target,
+                    VarValue::StringValue(mode_value.clone()),
+                ));
+            }
+            OptType::Assignment(target) | OptType::OptionalAssignment(target, _) => {
+                let value = match (value, &sub_cfg.opt_type) {
+                    (Some(v), _) => v,
+                    (None, OptType::OptionalAssignment(_, fallback)) => fallback.clone(),
+                    (None, _) => return Err(format!("Missing value for {}", what)),
+                };
+                for v in sub_cfg.split_value(value) {
+                    let v = check_value(&sub_cfg.value_restriction, &what, v)?;
+                    shell_code.push(assign_target(target, v));
+                }
+            }
+            // rejected while parsing the option definition
+            _ => panic!("Unsupported sub-option type"),
+        }
+    }
+
+    check_options_usage(sub_cfg_list)
+        .map_err(|msg| format!("{} (sub-option of {})", msg, oc.options_string()))
 }
 
 /// If counter is not None, creates the counter assignment.
@@ -432,7 +1407,7 @@ fn counter_assign<'a>(
     counter: Option<(&'a OptTarget, u16)>,
 ) -> Option<(&'a OptTarget, u16)> {
     if let Some((target, value)) = counter {
-        shell_code.push(assign_target(target, VarValue::IntValue(value as i32)));
+        shell_code.push(assign_target(target, VarValue::IntValue(value as i64)));
         None
     } else {
         counter
@@ -446,16 +1421,31 @@ fn counter_assign<'a>(
 /// * duplicate options
 /// * duplicate usage of variables/functions (only allowed for ModeSwitch)
 /// * ModeSwitch with same value
+/// * default values for functions or multiple default values for a ModeSwitch
+/// * fallback values of OptionalAssignments that don't match the value restriction
+/// * negated long options (`--no-<long>`) that clash with other long options
+/// * negatable options without long option
+/// * long options that only differ in case, if `ignore_case` is true
+/// * multiple numeric options or a numeric option together with digit short options
 ///
 /// Does not allow function and variable with same name. For a shell script
 /// this should work, but in our context it is most likely an error.
-fn validate_option_definitions(opt_def_list: &Vec<OptConfig>) {
+fn validate_option_definitions(opt_def_list: &[&OptConfig], ignore_case: bool) {
     let mut all_short_options = String::new();
-    let mut all_long_options: Vec<&String> = vec![];
+    let mut all_long_options: Vec<String> = vec![];
     let mut all_variables: Vec<(String, bool, bool)> = vec![];
     let mut mode_values_map: HashMap<String, Vec<&String>> = HashMap::new();
+    let mut default_names: Vec<String> = vec![];
+    let mut negated_long_options: Vec<String> = vec![];
+    let mut has_numeric = false;
 
     for oc in opt_def_list {
+        if oc.numeric {
+            if has_numeric {
+                die_internal("Duplicate definition of numeric option".to_string());
+            }
+            has_numeric = true;
+        }
         for chr in oc.opt_chars.chars() {
             match all_short_options.find(chr) {
                 Some(_) => {
@@ -467,12 +1457,26 @@ fn validate_option_definitions(opt_def_list: &Vec<OptConfig>) {
             }
         }
         for lng in &oc.opt_strings {
-            if all_long_options.contains(&lng) {
+            let lng_key = if ignore_case {
+                lng.to_lowercase()
+            } else {
+                lng.clone()
+            };
+            if all_long_options.contains(&lng_key) {
                 die_internal(format!("Duplicate definition of option '--{}'", lng));
             } else {
-                all_long_options.push(lng);
+                all_long_options.push(lng_key);
+            }
+            if oc.negatable {
+                negated_long_options.push(format!("no-{}", lng));
             }
         }
+        if oc.negatable && oc.opt_strings.is_empty() {
+            die_internal(format!(
+                "Negation requires a long option: {}",
+                oc.options_string()
+            ));
+        }
 
         let name = oc.get_target_name();
         let is_function = oc.is_target_function();
@@ -497,6 +1501,28 @@ fn validate_option_definitions(opt_def_list: &Vec<OptConfig>) {
                 all_variables.push((name.clone(), is_function, is_mode_switch));
             }
         }
+        if oc.default_value.is_some() {
+            if is_function {
+                die_internal(format!(
+                    "Default value not supported for function '{}'",
+                    name
+                ));
+            }
+            if oc.is_target_array() {
+                die_internal(format!("Default value not supported for array '{}'", name));
+            }
+            if default_names.contains(&name) {
+                die_internal(format!("Multiple default values for mode '{}'", name));
+            }
+            default_names.push(name.clone());
+        }
+
+        if let OptType::OptionalAssignment(_, fallback) = &oc.opt_type {
+            if let Err(msg) = check_option_value(oc, fallback.clone()) {
+                die_internal(format!("Fallback value rejected: {}", msg));
+            }
+        }
+
         if let OptType::ModeSwitch(_, value) = &oc.opt_type {
             if mode_values_map.contains_key(&name) {
                 if let Some(v) = mode_values_map.get(&name) {
@@ -509,6 +1535,117 @@ fn validate_option_definitions(opt_def_list: &Vec<OptConfig>) {
             }
         }
     }
+
+    if let (true, Some(chr)) = (
+        has_numeric,
+        all_short_options.chars().find(|c| c.is_ascii_digit()),
+    ) {
+        die_internal(format!(
+            "Short option '-{}' clashes with numeric option",
+            chr
+        ));
+    }
+
+    for lng in &negated_long_options {
+        let lng_key = if ignore_case {
+            lng.to_lowercase()
+        } else {
+            lng.clone()
+        };
+        if all_long_options.contains(&lng_key) {
+            die_internal(format!(
+                "Negated option '--{}' clashes with explicitly defined option",
+                lng
+            ));
+        }
+    }
+
+    // Sub-options have their own option names, but their variables must not
+    // be used by options or other sub-options.
+    for sub_cfg_list in opt_def_list.iter().filter_map(|oc| oc.get_sub_options()) {
+        validate_option_definitions(&sub_cfg_list.iter().collect::<Vec<_>>(), ignore_case);
+        let mut sub_names: Vec<String> = vec![];
+        for sub_cfg in sub_cfg_list {
+            let name = sub_cfg.get_target_name();
+            if all_variables.iter().any(|x| x.0 == name) {
+                die_internal(format!("Duplicate usage of variable/function '{}'", name));
+            }
+            sub_names.push(name);
+        }
+        for name in sub_names {
+            all_variables.push((name, false, false));
+        }
+    }
+}
+
+/// Validate the argument definitions.
+///
+/// The variables of the arguments must not be used by options or for the subcommand.
+fn validate_argument_definitions(
+    arg_cfg_list: &[ArgConfig],
+    opt_cfg_list: &[OptConfig],
+    subcommands: &[Subcommand],
+    cmd_line_args: &CmdLineArgs,
+) {
+    let mut used_names: Vec<String> = opt_cfg_list
+        .iter()
+        .chain(subcommands.iter().flat_map(|sc| sc.opt_cfg_list.iter()))
+        .map(|oc| oc.get_target_name())
+        .collect();
+    if !subcommands.is_empty() {
+        used_names.push(cmd_line_args.subcommand_var.clone());
+    }
+
+    for ac in arg_cfg_list {
+        if used_names.contains(&ac.name) {
+            die_internal(format!(
+                "Duplicate usage of variable/function '{}'",
+                ac.name
+            ));
+        }
+    }
+}
+
+/// Parses the subcommand definitions given with `--subcommand`.
+/// A subcommand definition is the name of the subcommand optionally followed
+/// by a colon and the option definitions of the subcommand.
+///
+/// Exits on invalid definitions.
+fn parse_subcommands(cmd_line_args: &CmdLineArgs) -> Vec<Subcommand> {
+    let mut subcommands: Vec<Subcommand> = vec![];
+
+    for sc_def in cmd_line_args.subcommands.iter().flatten() {
+        let (name, opt_def_str) = sc_def.split_once(':').unwrap_or((sc_def, ""));
+
+        if name.is_empty() || name.starts_with('-') || name.contains(char::is_whitespace) {
+            die_internal(format!("Invalid subcommand name '{}'", name));
+        }
+        if subcommands.iter().any(|sc| sc.name == name) {
+            die_internal(format!("Duplicate definition of subcommand '{}'", name));
+        }
+
+        let opt_cfg_list = match opt_def::parse(opt_def_str) {
+            Ok(list) => list,
+            Err(error) => die_internal(format!(
+                "Error parsing option definition of subcommand '{}':\n{}",
+                name, error
+            )),
+        };
+
+        subcommands.push(Subcommand {
+            name: name.to_string(),
+            opt_cfg_list,
+        });
+    }
+
+    subcommands
+}
+
+/// Makes a name list value restriction (`L=...`) case-insensitive, like `Li=...`.
+fn ignore_case_names(value_restriction: &mut Option<ValueRestriction>) {
+    if let Some(ValueRestriction::NameSet(_, ignore_case)) = value_restriction {
+        *ignore_case = true;
+    }
 }
 
 /// The actual parseargs logic.
@@ -534,7 +1671,94 @@ fn parseargs(cmd_line_args: CmdLineArgs) -> ! {
         }
     };
 
-    validate_option_definitions(&opt_cfg_list);
+    if cmd_line_args.ignore_case {
+        opt_cfg_list
+            .iter_mut()
+            .for_each(|oc| ignore_case_names(&mut oc.value_restriction));
+    }
+    validate_option_definitions(
+        &opt_cfg_list.iter().collect::<Vec<_>>(),
+        cmd_line_args.ignore_case,
+    );
+
+    if let (Some(min), Some(max)) = (cmd_line_args.min_args, cmd_line_args.max_args) {
+        if min > max {
+            die_internal(format!(
+                "Minimum number of arguments ({}) greater than maximum ({})",
+                min, max
+            ));
+        }
+    }
+    if let (Some(min), Some(max)) = (cmd_line_args.min_remainder, cmd_line_args.max_remainder) {
+        if min > max {
+            die_internal(format!(
+                "Minimum number of remainder arguments ({}) greater than maximum ({})",
+                min, max
+            ));
+        }
+    }
+
+    let mut subcommands = parse_subcommands(&cmd_line_args);
+    for sc in subcommands.iter_mut() {
+        if cmd_line_args.ignore_case {
+            sc.opt_cfg_list
+                .iter_mut()
+                .for_each(|oc| ignore_case_names(&mut oc.value_restriction));
+        }
+        // the options of a subcommand must fit to the global options
+        validate_option_definitions(
+            &opt_cfg_list
+                .iter()
+                .chain(sc.opt_cfg_list.iter())
+                .collect::<Vec<_>>(),
+            cmd_line_args.ignore_case,
+        );
+    }
+    if !subcommands.is_empty() {
+        let all_options = opt_cfg_list
+            .iter()
+            .chain(subcommands.iter().flat_map(|sc| sc.opt_cfg_list.iter()));
+        for oc in all_options {
+            if oc.get_target_name() == cmd_line_args.subcommand_var {
+                die_internal(format!(
+                    "Duplicate usage of variable/function '{}'",
+                    cmd_line_args.subcommand_var
+                ));
+            }
+        }
+    }
+
+    let mut arg_cfg_list = match &cmd_line_args.args {
+        Some(arg_def_str) => match opt_def::parse_args(arg_def_str) {
+            Ok(list) => list,
+            Err(error) => die_internal(format!("Error parsing argument definition:\n{}", error)),
+        },
+        None => vec![],
+    };
+    if cmd_line_args.ignore_case {
+        arg_cfg_list
+            .iter_mut()
+            .for_each(|ac| ignore_case_names(&mut ac.value_restriction));
+    }
+    validate_argument_definitions(&arg_cfg_list, &opt_cfg_list, &subcommands, &cmd_line_args);
+
+    if let UnknownPolicy::Collect(array) = &cmd_line_args.unknown {
+        let all_options = opt_cfg_list
+            .iter()
+            .chain(subcommands.iter().flat_map(|sc| sc.opt_cfg_list.iter()));
+        for oc in all_options {
+            if oc.get_target_name() == *array {
+                die_internal(format!("Duplicate usage of variable/function '{}'", array));
+            }
+        }
+    }
+
+    if let Some(prefix) = &cmd_line_args.env_prefix {
+        apply_env_prefix(&mut opt_cfg_list, prefix);
+        for sc in &mut subcommands {
+            apply_env_prefix(&mut sc.opt_cfg_list, prefix);
+        }
+    }
 
     // Add support for `--help` if requested.
     // As this is added to the end of the list, a custom '--help' has precedence.
@@ -543,10 +1767,18 @@ fn parseargs(cmd_line_args: CmdLineArgs) -> ! {
             opt_chars: "".to_string(),
             opt_strings: vec!["help".to_string()],
             opt_type: OptType::Help(OptTarget::Function("show_help".to_string())),
+            value_restriction: None,
+            default_value: None,
             required: false,
             singleton: true,
+            negatable: false,
+            env_var: None,
+            numeric: false,
+            separator: None,
+            sub_option: false,
             assigned: Cell::new(false),
             count_value: Cell::new(0),
+            plus_given: Cell::new(false),
         });
     }
     // Add support for `--version` if requested.
@@ -556,10 +1788,18 @@ fn parseargs(cmd_line_args: CmdLineArgs) -> ! {
             opt_chars: "".to_string(),
             opt_strings: vec!["version".to_string()],
             opt_type: OptType::Help(OptTarget::Function("show_version".to_string())),
+            value_restriction: None,
+            default_value: None,
             required: false,
             singleton: true,
+            negatable: false,
+            env_var: None,
+            numeric: false,
+            separator: None,
+            sub_option: false,
             assigned: Cell::new(false),
             count_value: Cell::new(0),
+            plus_given: Cell::new(false),
         });
     }
 
@@ -567,6 +1807,12 @@ fn parseargs(cmd_line_args: CmdLineArgs) -> ! {
         for oc in &opt_cfg_list {
             eprintln!("{:?}", oc);
         }
+        for sc in &subcommands {
+            eprintln!("Subcommand: {}", sc.name);
+            for oc in &sc.opt_cfg_list {
+                eprintln!("{:?}", oc);
+            }
+        }
     }
 
     // Determine shell. Either from option, environment var or the default.
@@ -592,16 +1838,72 @@ fn parseargs(cmd_line_args: CmdLineArgs) -> ! {
             shell
         ));
     }
+    if let (false, UnknownPolicy::Collect(_)) = (shell_tmpl.supports_arrays, &cmd_line_args.unknown)
+    {
+        die_internal(format!(
+            "Shell {} does not support arrays, so option --unknown=collect is not supported",
+            shell
+        ));
+    }
+    if !shell_tmpl.supports_arrays {
+        let array_option = opt_cfg_list
+            .iter()
+            .chain(subcommands.iter().flat_map(|sc| sc.opt_cfg_list.iter()))
+            .find(|oc| oc.is_target_array());
+        if let Some(oc) = array_option {
+            die_internal(format!(
+                "Shell {} does not support arrays, so array option {} is not supported",
+                shell,
+                oc.options_string()
+            ));
+        }
+        if let Some(ac) = arg_cfg_list.iter().find(|ac| ac.variadic) {
+            die_internal(format!(
+                "Shell {} does not support arrays, so variadic argument {} is not supported",
+                shell,
+                ac.display_name()
+            ));
+        }
+    }
+    if !shell_tmpl.supports_maps {
+        let map_option = opt_cfg_list
+            .iter()
+            .chain(subcommands.iter().flat_map(|sc| sc.opt_cfg_list.iter()))
+            .find(|oc| oc.is_target_map());
+        if let Some(oc) = map_option {
+            die_internal(format!(
+                "Shell {} does not support associative arrays, so map option {} is not supported",
+                shell,
+                oc.options_string()
+            ));
+        }
+    }
 
     let mut code: Vec<CodeChunk> = vec![];
 
     // generate initialization code. Check for functions, initialize variables
-    let mut init_code = shell_init_code(&opt_cfg_list, &cmd_line_args, cmd_line_args.init_vars);
+    let mut init_code = shell_init_code(
+        &opt_cfg_list,
+        &arg_cfg_list,
+        &cmd_line_args,
+        cmd_line_args.init_vars,
+    );
 
     // let options_code = parse_shell_options(&opt_cfg_list, &cmd_line_args);
-    let rc = match parse_shell_options(&mut opt_cfg_list, &cmd_line_args) {
-        Ok(mut c) => {
+    let rc = match parse_shell_options(
+        &mut opt_cfg_list,
+        &subcommands,
+        &arg_cfg_list,
+        &cmd_line_args,
+    ) {
+        Ok((mut c, subcommand_idx)) => {
             code.append(&mut init_code);
+            if let Some(idx) = subcommand_idx {
+                code.append(&mut options_init_code(
+                    &subcommands[idx].opt_cfg_list,
+                    cmd_line_args.init_vars,
+                ));
+            }
             code.append(&mut c);
             0
         }
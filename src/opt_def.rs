@@ -6,21 +6,29 @@
 //
 
 use crate::cmd_line::CmdLineElement;
+use regex_lite::RegexBuilder;
 use std::cell::Cell;
+use std::str::FromStr;
 
-/// Target for a option. Parseargs either assigns a variable or calls
-/// a function.
+/// Target for a option. Parseargs either assigns a variable, calls
+/// a function or adds to an array.
 #[derive(Debug, PartialEq)]
 pub enum OptTarget {
     Variable(String),
     Function(String),
+    /// Array variable. Only supported for assignments.
+    Array(String),
 }
 
-/// Option attributes. The `*` or `?` before the option target.
+/// Option attributes. The `*` or `?` and the `~` before the option target
+/// and the `/SEPARATOR` and `@ENV_VAR` after it.
 #[derive(Debug, PartialEq)]
 enum OptAttribute {
     Required,
     Singleton,
+    Negatable,
+    EnvVar(String),
+    Separator(String),
 }
 
 /// Type of the option.
@@ -33,13 +41,51 @@ pub enum OptType {
     ModeSwitch(OptTarget, String),
     /// Assignment option that requires an argument. Like `-o outfile`.
     Assignment(OptTarget),
+    /// Assignment option with an optional argument. Like `--color` or `--color=always`.
+    /// The argument must be given with the option (`--color=always`, `-calways`).
+    /// Without argument, the fallback value is assigned.
+    OptionalAssignment(OptTarget, String),
     /// Counting occuences on the command line. Like -v, -vvv, -v  -vvv,...
     Counter(OptTarget),
+    /// Forwards all remaining command line arguments to an array or a function.
+    /// Like `--exec cmd args...`. The target is never a variable.
+    Forward(OptTarget),
+    /// Key/value assignment. The option-argument is split at the first `=`.
+    /// Like `-D key=value`. The target variable is an associative array, a
+    /// function is called with key and value.
+    Map(OptTarget),
+    /// Assignment whose option-argument is a comma-separated list of sub-options.
+    /// Like `-o ro,uid=1000`. The sub-options are defined like options.
+    SubOptions(OptTarget, Vec<OptConfig>),
     /// Help related option used for --help and --version. Cannot be created
     /// by the user directly, only by requesting help/version support.
     Help(OptTarget),
 }
 
+/// Restriction of the values accepted by an assignment option.
+/// The `=...` after the target of an assignment.
+#[derive(Debug, PartialEq)]
+pub enum ValueRestriction {
+    /// The value must be one of the given names. Like `L=copy|move`.
+    /// The flag tells whether the names are compared case-insensitive (`Li=...`).
+    NameSet(Vec<String>, bool),
+    /// The whole value must match the given regular expression. Like `R=[0-9]+`.
+    /// The flag tells whether the match is case-insensitive (`Ri=...`).
+    RegEx(String, bool),
+    /// The whole value must match the given shell glob pattern. Like `G=*.csv`.
+    /// The flag tells whether the match is case-insensitive (`Gi=...`).
+    Glob(String, bool),
+    /// The value must be an integer. Optionally in the range min to max (inclusive).
+    /// Like `I`, `I=-5<100` or the short form `-5<100`.
+    Integer(Option<i64>, Option<i64>),
+    /// The value must be an unsigned integer. Optionally in the range min to max (inclusive).
+    /// Like `U` or `U=1<65535`.
+    Unsigned(Option<u64>, Option<u64>),
+    /// The value must be a floating point number. Optionally in the range min to max (inclusive).
+    /// Like `F` or `F=0.5<`.
+    Float(Option<f64>, Option<f64>),
+}
+
 /// Describes a supported option.
 #[derive(Debug, PartialEq)]
 pub struct OptConfig {
@@ -49,16 +95,32 @@ pub struct OptConfig {
     pub opt_strings: Vec<String>,
     // type of option
     pub opt_type: OptType,
+    // restriction of the accepted values (only assignments)
+    pub value_restriction: Option<ValueRestriction>,
+    // default value assigned before the command line is processed
+    pub default_value: Option<String>,
     // whether this option is required
     pub required: bool,
     // Whether this is a singleton option. If a singleton option is found, only its action is
     // executed and all other options and arguments are dropped (including other singletons).
     // Typically used for '--help' etc.
     pub singleton: bool,
+    // Whether the long options are also accepted with the prefix `no-`. Like `--no-color`.
+    pub negatable: bool,
+    // environment variable that supplies the value if the option is not given
+    pub env_var: Option<String>,
+    // Whether the option is also accepted as numeric option. Like `-20` for `--lines=20`.
+    pub numeric: bool,
+    // separator to split the option-argument into multiple values (only arrays and functions)
+    pub separator: Option<String>,
+    // Whether this is a sub-option given in the option-argument of another option.
+    pub sub_option: bool,
     // Runtime: Whether this variable has been set
     pub assigned: Cell<bool>,
     // Runtime: Count of a counting variable
     pub count_value: Cell<u16>,
+    // Runtime: Whether the flag was last given as plus option. Like `+x`.
+    pub plus_given: Cell<bool>,
 }
 
 impl OptConfig {
@@ -69,48 +131,85 @@ impl OptConfig {
     pub fn match_option(&self, el: &CmdLineElement) -> bool {
         match el {
             CmdLineElement::ShortOption(c) => self.opt_chars.find(*c).is_some(),
-            CmdLineElement::LongOption(s) => self.opt_strings.contains(s),
-            CmdLineElement::LongOptionValue(s, _) => self.opt_strings.contains(s),
+            // `+x` only turns off flags
+            CmdLineElement::PlusOption(c) => {
+                matches!(self.opt_type, OptType::Flag(_)) && self.opt_chars.find(*c).is_some()
+            }
+            CmdLineElement::LongOption(s) => {
+                self.opt_strings.contains(s) || self.is_negated_option(el)
+            }
+            CmdLineElement::LongOptionValue(s, _) => {
+                self.opt_strings.contains(s) || self.is_negated_option(el)
+            }
+            CmdLineElement::NumericOption(_) => self.numeric,
+            _ => false,
+        }
+    }
+
+    /// Returns whether the given command line element is the negated form
+    /// (`--no-<long>`) of a long option of this negatable option.
+    pub fn is_negated_option(&self, el: &CmdLineElement) -> bool {
+        if !self.negatable {
+            return false;
+        }
+        match el {
+            CmdLineElement::LongOption(s) | CmdLineElement::LongOptionValue(s, _) => s
+                .strip_prefix("no-")
+                .is_some_and(|name| self.opt_strings.iter().any(|o| o == name)),
             _ => false,
         }
     }
 
     /// Returns whether duplicate usage of this option is allowed.
-    /// This is allowed for Counter options and options with a target type Function.
+    /// This is allowed for Counter and Map options and options with a target type
+    /// Function or Array.
     pub fn is_duplicate_allowed(&self) -> bool {
-        matches!(self.opt_type, OptType::Counter(_))
+        matches!(self.opt_type, OptType::Counter(_) | OptType::Map(_))
+            || self.is_target_array()
             || matches!(
                 self.opt_type,
                 OptType::Flag(OptTarget::Function(_))
                     | OptType::ModeSwitch(OptTarget::Function(_), _)
                     | OptType::Assignment(OptTarget::Function(_))
+                    | OptType::OptionalAssignment(OptTarget::Function(_), _)
                     | OptType::Counter(OptTarget::Function(_))
+                    | OptType::Forward(OptTarget::Function(_))
+                    | OptType::SubOptions(OptTarget::Function(_), _)
             )
     }
 
     /// Returns the name of the option target. The name could represent a
-    /// variable or a function.
+    /// variable, a function or an array.
     pub fn get_target_name(&self) -> String {
-        match &self.opt_type {
-            OptType::Flag(OptTarget::Function(name))
-            | OptType::Flag(OptTarget::Variable(name))
-            | OptType::Assignment(OptTarget::Function(name))
-            | OptType::Assignment(OptTarget::Variable(name))
-            | OptType::Counter(OptTarget::Function(name))
-            | OptType::Counter(OptTarget::Variable(name))
-            | OptType::ModeSwitch(OptTarget::Function(name), _)
-            | OptType::ModeSwitch(OptTarget::Variable(name), _)
-            | OptType::Help(OptTarget::Variable(name))
-            | OptType::Help(OptTarget::Function(name)) => name.clone(),
+        match self.get_target() {
+            OptTarget::Variable(name) | OptTarget::Function(name) | OptTarget::Array(name) => {
+                name.clone()
+            }
         }
     }
 
+    /// Returns whether the option can get its value from an environment variable.
+    /// This is supported for flags, assignments and counters.
+    pub fn is_env_var_supported(&self) -> bool {
+        matches!(
+            self.opt_type,
+            OptType::Flag(_)
+                | OptType::Assignment(_)
+                | OptType::OptionalAssignment(_, _)
+                | OptType::Counter(_)
+        )
+    }
+
     /// Returns the option target
     pub fn get_target(&self) -> &OptTarget {
         match &self.opt_type {
             OptType::Flag(ot)
             | OptType::Assignment(ot)
+            | OptType::OptionalAssignment(ot, _)
             | OptType::Counter(ot)
+            | OptType::Forward(ot)
+            | OptType::Map(ot)
+            | OptType::SubOptions(ot, _)
             | OptType::ModeSwitch(ot, _)
             | OptType::Help(ot) => ot,
         }
@@ -122,20 +221,93 @@ impl OptConfig {
             &self.opt_type,
             OptType::Flag(OptTarget::Function(_))
                 | OptType::Assignment(OptTarget::Function(_))
+                | OptType::OptionalAssignment(OptTarget::Function(_), _)
                 | OptType::Counter(OptTarget::Function(_))
                 | OptType::ModeSwitch(OptTarget::Function(_), _)
+                | OptType::Forward(OptTarget::Function(_))
+                | OptType::Map(OptTarget::Function(_))
+                | OptType::SubOptions(OptTarget::Function(_), _)
         )
     }
 
     /// Returns whether the option target is a variable.
+    /// This includes array variables.
     pub fn is_target_variable(&self) -> bool {
         !self.is_target_function()
     }
 
+    /// Returns whether the option target is an array variable.
+    pub fn is_target_array(&self) -> bool {
+        matches!(self.get_target(), OptTarget::Array(_))
+    }
+
+    /// Splits the given option-argument at the separator of this option.
+    /// A backslash escapes the separator and the backslash itself.
+    /// Empty elements are dropped.
+    ///
+    /// Without separator the value is returned as single element.
+    pub fn split_value(&self, value: String) -> Vec<String> {
+        let separator = match &self.separator {
+            Some(sep) => sep,
+            None => return vec![value],
+        };
+
+        let mut elements = Vec::new();
+        let mut current = String::new();
+        let mut rest = value.as_str();
+        while let Some(c) = rest.chars().next() {
+            if let Some(after) = rest.strip_prefix('\\') {
+                if let Some(r) = after.strip_prefix(separator.as_str()) {
+                    current.push_str(separator);
+                    rest = r;
+                    continue;
+                } else if let Some(r) = after.strip_prefix('\\') {
+                    current.push('\\');
+                    rest = r;
+                    continue;
+                }
+            }
+            if let Some(r) = rest.strip_prefix(separator.as_str()) {
+                if !current.is_empty() {
+                    elements.push(std::mem::take(&mut current));
+                }
+                rest = r;
+            } else {
+                current.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+        if !current.is_empty() {
+            elements.push(current);
+        }
+        elements
+    }
+
+    /// Returns whether the option target is an associative array variable.
+    pub fn is_target_map(&self) -> bool {
+        matches!(self.opt_type, OptType::Map(OptTarget::Variable(_)))
+    }
+
+    /// Returns the sub-options, if this option has sub-options.
+    pub fn get_sub_options(&self) -> Option<&Vec<OptConfig>> {
+        match &self.opt_type {
+            OptType::SubOptions(_, sub_cfg_list) => Some(sub_cfg_list),
+            _ => None,
+        }
+    }
+
     /// Formats the option for display. Most likely in error messages.
     /// If the short option is `-l` and long `--long` it will return
-    /// something like `-l/--long`.
+    /// something like `-l/--long`. For a negatable option it returns
+    /// `-l/--[no-]long`.
     pub fn options_string(&self) -> String {
+        // sub-options are given without dashes
+        if self.sub_option {
+            let mut names: Vec<String> = self.opt_chars.chars().map(String::from).collect();
+            names.extend(self.opt_strings.iter().cloned());
+            return names.join("/");
+        }
+
         let mut sb = String::new();
 
         if !self.opt_chars.is_empty() {
@@ -152,14 +324,44 @@ impl OptConfig {
             if !self.opt_chars.is_empty() {
                 sb.push('/');
             }
-            sb.push_str("--");
-            sb.push_str(&self.opt_strings.join("/--"));
+            let prefix = if self.negatable { "--[no-]" } else { "--" };
+            sb.push_str(prefix);
+            sb.push_str(&self.opt_strings.join(&format!("/{}", prefix)));
+        }
+
+        if self.numeric {
+            if !sb.is_empty() {
+                sb.push('/');
+            }
+            sb.push_str("-NUM");
         }
 
         sb
     }
 }
 
+/// Describes a named positional argument.
+#[derive(Debug, PartialEq)]
+pub struct ArgConfig {
+    /// Name of the variable (or array) the argument is assigned to.
+    pub name: String,
+    /// Whether the argument is optional. Marked with `?`.
+    pub optional: bool,
+    /// Whether the argument collects all remaining arguments in an array.
+    /// Marked with `...`.
+    pub variadic: bool,
+    /// restriction of the accepted values
+    pub value_restriction: Option<ValueRestriction>,
+}
+
+impl ArgConfig {
+    /// Returns the name of the argument for display in error messages.
+    /// This is the name in upper case.
+    pub fn display_name(&self) -> String {
+        self.name.to_uppercase()
+    }
+}
+
 /// Configuration of the option definition parser.
 pub struct ParserConfig {
     /// Whether UTF-8 characters are allowed as option characters.
@@ -178,6 +380,9 @@ pub struct ParserSource {
     /// Stack to push/pop a position in the character sequence.
     position_stack: Vec<usize>,
 
+    /// Whether a sub-option list is parsed. Then an unquoted word also ends at `}`.
+    in_sub_options: bool,
+
     /// Configuration of the parser
     config: ParserConfig,
 }
@@ -192,6 +397,7 @@ impl ParserSource {
             index: 0,
             length: len,
             position_stack: Vec::new(),
+            in_sub_options: false,
             config: ParserConfig {
                 // Don't allow UTF-8 chars in options. This would most likely
                 // result in portability problems. What if the parseargs option
@@ -400,30 +606,121 @@ fn parse_name(ps: &mut ParserSource) -> Result<String, ParsingError> {
     Ok(name)
 }
 
-/// Parses a value. Currently a value is the same as a name.
+/// Parses a string delimited by single or double quotes. Within the quotes a
+/// backslash escapes the quote character and a backslash. Any other backslash
+/// is kept as it is (as regular expressions need them).
+///
+/// On a missing closing quote, the source position is set to the opening quote.
+fn parse_quoted_string(ps: &mut ParserSource) -> Result<String, ParsingError> {
+    ps.push_pos();
+    let quote = match ps.next_if(|c| c == '\'' || c == '"') {
+        Some(q) => q,
+        None => {
+            ps.drop_pos();
+            return Err(ParsingError::Empty);
+        }
+    };
+
+    let mut string = String::new();
+    loop {
+        let c = match ps.next() {
+            Some('\\') => match ps.peek() {
+                Some(e) if e == quote || e == '\\' => ps.next(),
+                _ => Some('\\'),
+            },
+            Some(c) if c == quote => break,
+            c => c,
+        };
+        match c {
+            Some(c) => string.push(c),
+            None => {
+                // position after the opening quote, so the error points to it
+                ps.pop_pos();
+                ps.next();
+                return Err(ParsingError::Error(
+                    "Unterminated quoted string".to_string(),
+                ));
+            }
+        }
+    }
+
+    ps.drop_pos();
+    Ok(string)
+}
+
+/// Parses a value. A value is either a name or a single- or double-quoted string.
 fn parse_value(ps: &mut ParserSource) -> Result<String, ParsingError> {
-    // TODO: This should be extended to also parse strings delimited by single or double quote.
-    parse_name(ps)
+    match ps.peek() {
+        Some('\'') | Some('"') => parse_quoted_string(ps),
+        _ => parse_name(ps),
+    }
 }
 
-/// Parse the option attribute `*` (required) or `?` (singleton).
-fn parse_option_attribute(ps: &mut ParserSource) -> Option<OptAttribute> {
+/// Parse the option attributes. Either `*` (required) or `?` (singleton),
+/// optionally followed by `~` (negatable).
+fn parse_option_attributes(ps: &mut ParserSource) -> Vec<OptAttribute> {
+    let mut attrs = Vec::new();
     match ps.next_if(|c| c == '*' || c == '?') {
-        Some('*') => Some(OptAttribute::Required),
-        Some('?') => Some(OptAttribute::Singleton),
-        _ => None,
+        Some('*') => attrs.push(OptAttribute::Required),
+        Some('?') => attrs.push(OptAttribute::Singleton),
+        _ => (),
+    }
+    if ps.next_if(|c| c == '~').is_some() {
+        attrs.push(OptAttribute::Negatable);
+    }
+    attrs
+}
+
+/// Parse the name of the environment variable that supplies the option value.
+/// Like `@DEPLOY_TOKEN`. Returns an empty vector if no environment variable is given.
+fn parse_env_var(ps: &mut ParserSource) -> Result<Vec<OptAttribute>, ParsingError> {
+    if ps.next_if(|c| c == '@').is_none() {
+        return Ok(Vec::new());
+    }
+    match parse_name(ps) {
+        Ok(name) => Ok(vec![OptAttribute::EnvVar(name)]),
+        Err(ParsingError::Empty) => Err(ParsingError::Error(
+            "environment variable name expected after this".to_string(),
+        )),
+        Err(ParsingError::Error(msg)) => Err(ParsingError::Error(msg)),
     }
 }
 
+/// Parses the separator to split the option-argument: `/` followed by a
+/// single character or a quoted string. A `,` has to be escaped (`/\,`).
+fn parse_separator(ps: &mut ParserSource) -> Result<Vec<OptAttribute>, ParsingError> {
+    if ps.next_if(|c| c == '/').is_none() {
+        return Ok(Vec::new());
+    }
+    let separator = match ps.peek() {
+        Some('\'') | Some('"') => parse_quoted_string(ps)?,
+        Some('\\') => {
+            ps.next();
+            ps.next().map(String::from).unwrap_or_default()
+        }
+        Some(c) if c != ',' => {
+            ps.next();
+            c.to_string()
+        }
+        _ => String::new(),
+    };
+    if separator.is_empty() {
+        return Err(ParsingError::Error(
+            "separator expected after this".to_string(),
+        ));
+    }
+    Ok(vec![OptAttribute::Separator(separator)])
+}
+
 /// Parse a Flag or a Mode-Option.
-fn parse_flag_mode(ps: &mut ParserSource) -> Result<(OptType, Option<OptAttribute>), ParsingError> {
+fn parse_flag_mode(ps: &mut ParserSource) -> Result<(OptType, Vec<OptAttribute>), ParsingError> {
     // must start with `#`
     match ps.next() {
         Some('#') => (),
         _ => Err(ParsingError::Empty)?,
     }
 
-    let attr = parse_option_attribute(ps);
+    let mut attr = parse_option_attributes(ps);
 
     let target_name = match parse_name(ps) {
         Ok(name) => name,
@@ -442,8 +739,18 @@ fn parse_flag_mode(ps: &mut ParserSource) -> Result<(OptType, Option<OptAttribut
         OptTarget::Variable(target_name)
     };
 
+    attr.append(&mut parse_env_var(ps)?);
+
     if ps.next_if(|c| c == '=').is_none() {
         Ok((OptType::Flag(target), attr))
+    } else if attr.contains(&OptAttribute::Negatable) {
+        Err(ParsingError::Error(
+            "Negation ('~') not supported for mode switch".to_string(),
+        ))
+    } else if attr.iter().any(|a| matches!(a, OptAttribute::EnvVar(_))) {
+        Err(ParsingError::Error(
+            "Environment variable not supported for mode switch".to_string(),
+        ))
     } else {
         match parse_value(ps) {
             Ok(value) => Ok((OptType::ModeSwitch(target, value), attr)),
@@ -454,15 +761,17 @@ fn parse_flag_mode(ps: &mut ParserSource) -> Result<(OptType, Option<OptAttribut
 }
 
 /// Parse an assignment.
-fn parse_assignment(
-    ps: &mut ParserSource,
-) -> Result<(OptType, Option<OptAttribute>), ParsingError> {
+fn parse_assignment(ps: &mut ParserSource) -> Result<(OptType, Vec<OptAttribute>), ParsingError> {
     match ps.next() {
         Some('=') => (),
         _ => Err(ParsingError::Empty)?,
     }
 
-    let attr = parse_option_attribute(ps);
+    let mut attr = parse_option_attributes(ps);
+
+    // `@` marks an array target, `%` a map option
+    let is_array = ps.next_if(|c| c == '@').is_some();
+    let is_map = !is_array && ps.next_if(|c| c == '%').is_some();
 
     let target_name = match parse_name(ps) {
         Ok(name) => name,
@@ -473,7 +782,10 @@ fn parse_assignment(
     };
 
     ps.push_pos();
-    let target = if ps.next() == Some('(') && ps.next() == Some(')') {
+    let target = if is_array {
+        ps.drop_pos();
+        OptTarget::Array(target_name)
+    } else if ps.next() == Some('(') && ps.next() == Some(')') {
         ps.drop_pos();
         OptTarget::Function(target_name)
     } else {
@@ -481,17 +793,306 @@ fn parse_assignment(
         OptTarget::Variable(target_name)
     };
 
-    Ok((OptType::Assignment(target), attr))
+    if is_map {
+        if attr.contains(&OptAttribute::Negatable) && matches!(target, OptTarget::Function(_)) {
+            Err(ParsingError::Error(
+                "Negation ('~') not supported for map options with function".to_string(),
+            ))?
+        }
+        return Ok((OptType::Map(target), attr));
+    }
+
+    // `{...}` defines the sub-options given in the option-argument
+    if ps.next_if(|c| c == '{').is_some() {
+        let sub_cfg_list = parse_sub_option_list(ps)?;
+        return Ok((OptType::SubOptions(target, sub_cfg_list), attr));
+    }
+
+    // `...` forwards the rest of the command line to an array or function
+    ps.push_pos();
+    if ps.next() == Some('.') && ps.next() == Some('.') && ps.next() == Some('.') {
+        ps.drop_pos();
+        return match target {
+            OptTarget::Variable(_) => Err(ParsingError::Error(
+                "Forwarding ('...') requires an array or a function".to_string(),
+            )),
+            _ => Ok((OptType::Forward(target), attr)),
+        };
+    }
+    ps.pop_pos();
+
+    let mut separator = parse_separator(ps)?;
+    if !separator.is_empty() && matches!(target, OptTarget::Variable(_)) {
+        return Err(ParsingError::Error(
+            "Separator ('/') requires an array or a function".to_string(),
+        ));
+    }
+    attr.append(&mut separator);
+
+    attr.append(&mut parse_env_var(ps)?);
+
+    // optional option-argument with fallback value: `?=value`
+    match parse_introduced_word(ps, "?=", "fallback value")? {
+        Some(fallback) => Ok((OptType::OptionalAssignment(target, fallback), attr)),
+        None => Ok((OptType::Assignment(target), attr)),
+    }
+}
+
+/// Parse a list of names separated by `|`. Like `copy|move|delete`.
+fn parse_name_set(ps: &mut ParserSource) -> Result<Vec<String>, ParsingError> {
+    let mut names = Vec::new();
+    loop {
+        match parse_value(ps) {
+            Ok(name) => names.push(name),
+            Err(ParsingError::Empty) => {
+                Err(ParsingError::Error("name expected after this".to_string()))?
+            }
+            Err(ParsingError::Error(msg)) => Err(ParsingError::Error(msg))?,
+        }
+        if ps.next_if(|c| c == '|').is_none() {
+            break;
+        }
+    }
+    Ok(names)
+}
+
+/// Parses a string that is either quoted (see `parse_quoted_string`) or extends
+/// till the next unescaped comma (or the end of the source). In the latter case
+/// a backslash-escaped comma is unescaped, all other backslashes are kept as
+/// they are. Within a sub-option list the string also ends at an unescaped `}`.
+fn parse_string(ps: &mut ParserSource) -> Result<String, ParsingError> {
+    if let Some('\'') | Some('"') = ps.peek() {
+        return parse_quoted_string(ps);
+    }
+
+    let in_sub_options = ps.in_sub_options;
+    let is_end = |c: char| c == ',' || (in_sub_options && c == '}');
+
+    let mut string = String::new();
+
+    while let Some(c) = ps.next_if(|c| !is_end(c)) {
+        if c == '\\' {
+            if let Some(escaped) = ps.next_if(is_end) {
+                string.push(escaped);
+                continue;
+            }
+        }
+        string.push(c);
+    }
+
+    if string.is_empty() {
+        Err(ParsingError::Empty)
+    } else {
+        Ok(string)
+    }
+}
+
+/// Parse a single bound of a numeric range. Returns `None` if the bound is omitted.
+fn parse_range_bound<T: FromStr>(ps: &mut ParserSource) -> Result<Option<T>, ParsingError> {
+    let mut number = String::new();
+    while let Some(c) =
+        ps.next_if(|c| c.is_ascii_alphanumeric() || c == '-' || c == '+' || c == '.')
+    {
+        number.push(c);
+    }
+
+    if number.is_empty() {
+        Ok(None)
+    } else {
+        match number.parse::<T>() {
+            Ok(n) => Ok(Some(n)),
+            Err(_) => Err(ParsingError::Error(format!("Invalid number '{}'", number))),
+        }
+    }
+}
+
+/// Parse a numeric range like `-5<100`. Both bounds are optional, so `0<` or
+/// `<100` are valid ranges.
+fn parse_range<T: FromStr + PartialOrd>(
+    ps: &mut ParserSource,
+) -> Result<(Option<T>, Option<T>), ParsingError> {
+    let min = parse_range_bound(ps)?;
+    if ps.next_if(|c| c == '<').is_none() {
+        Err(ParsingError::Error("'<' expected after this".to_string()))?
+    }
+    let max = parse_range_bound(ps)?;
+
+    if let (Some(min), Some(max)) = (&min, &max) {
+        if min > max {
+            Err(ParsingError::Error(
+                "Invalid range: lower bound greater than upper bound".to_string(),
+            ))?
+        }
+    }
+    Ok((min, max))
+}
+
+/// Parse the optional range after a numeric value restriction. Like `=1<10`
+/// after `I`.
+fn parse_optional_range<T: FromStr + PartialOrd>(
+    ps: &mut ParserSource,
+) -> Result<(Option<T>, Option<T>), ParsingError> {
+    if ps.next_if(|c| c == '=').is_some() {
+        parse_range(ps)
+    } else {
+        Ok((None, None))
+    }
+}
+
+/// Parse the value restriction of an assignment. This is the part after the
+/// `=` following the target name. Like `L=copy|move`.
+fn parse_value_restriction(ps: &mut ParserSource) -> Result<ValueRestriction, ParsingError> {
+    let kind = match parse_name(ps) {
+        Ok(kind) => kind,
+        Err(ParsingError::Empty) => {
+            // short form of integer range: `-5<100`
+            if let Some(c) = ps.peek() {
+                if c.is_ascii_digit() || c == '-' || c == '<' {
+                    let (min, max) = parse_range(ps)?;
+                    return Ok(ValueRestriction::Integer(min, max));
+                }
+            }
+            Err(ParsingError::Error(
+                "value restriction expected after this".to_string(),
+            ))?
+        }
+        Err(ParsingError::Error(msg)) => Err(ParsingError::Error(msg))?,
+    };
+
+    match kind.as_str() {
+        "L" | "Li" => {
+            if ps.next_if(|c| c == '=').is_none() {
+                Err(ParsingError::Error("'=' expected after this".to_string()))?
+            }
+            let names = parse_name_set(ps)?;
+            Ok(ValueRestriction::NameSet(names, kind == "Li"))
+        }
+        "R" | "Ri" => {
+            if ps.next_if(|c| c == '=').is_none() {
+                Err(ParsingError::Error("'=' expected after this".to_string()))?
+            }
+            let pattern = match parse_string(ps) {
+                Ok(pattern) => pattern,
+                Err(ParsingError::Empty) => Err(ParsingError::Error(
+                    "regular expression expected after this".to_string(),
+                ))?,
+                Err(ParsingError::Error(msg)) => Err(ParsingError::Error(msg))?,
+            };
+            if let Err(e) = RegexBuilder::new(&pattern).build() {
+                Err(ParsingError::Error(format!(
+                    "Invalid regular expression: {}",
+                    e
+                )))?
+            }
+            Ok(ValueRestriction::RegEx(pattern, kind == "Ri"))
+        }
+        "G" | "Gi" => {
+            if ps.next_if(|c| c == '=').is_none() {
+                Err(ParsingError::Error("'=' expected after this".to_string()))?
+            }
+            match parse_string(ps) {
+                Ok(pattern) => Ok(ValueRestriction::Glob(pattern, kind == "Gi")),
+                Err(ParsingError::Empty) => Err(ParsingError::Error(
+                    "glob pattern expected after this".to_string(),
+                )),
+                Err(ParsingError::Error(msg)) => Err(ParsingError::Error(msg)),
+            }
+        }
+        "I" => {
+            let (min, max) = parse_optional_range(ps)?;
+            Ok(ValueRestriction::Integer(min, max))
+        }
+        "U" => {
+            let (min, max) = parse_optional_range(ps)?;
+            Ok(ValueRestriction::Unsigned(min, max))
+        }
+        "F" => {
+            let (min, max): (Option<f64>, Option<f64>) = parse_optional_range(ps)?;
+            if min.iter().chain(max.iter()).any(|n| !n.is_finite()) {
+                Err(ParsingError::Error(
+                    "Invalid range: bounds must be finite numbers".to_string(),
+                ))?
+            }
+            Ok(ValueRestriction::Float(min, max))
+        }
+        _ => Err(ParsingError::Error(format!(
+            "Unknown value restriction '{}'",
+            kind
+        ))),
+    }
+}
+
+/// Parses a word. A word is either a single- or double-quoted string or an unquoted
+/// sequence of characters that extends till the next `,`, `:=`, `=` or whitespace.
+/// Within a sub-option list it also ends at `}`.
+/// Like `-5`, `/tmp/out.txt`, `http://localhost` or `'a, b'`.
+fn parse_word(ps: &mut ParserSource) -> Result<String, ParsingError> {
+    if let Some('\'') | Some('"') = ps.peek() {
+        return parse_quoted_string(ps);
+    }
+    let in_sub_options = ps.in_sub_options;
+    let mut word = String::new();
+    while let Some(c) =
+        ps.next_if(|c| c != ',' && c != '=' && !c.is_whitespace() && !(in_sub_options && c == '}'))
+    {
+        // a `:` only ends the word if it starts a default value `:=`
+        if c == ':' && ps.peek() == Some('=') {
+            ps.back();
+            break;
+        }
+        word.push(c);
+    }
+    if word.is_empty() {
+        Err(ParsingError::Empty)
+    } else {
+        Ok(word)
+    }
+}
+
+/// Parse a value that is introduced by the two characters `intro`. Like the
+/// default value `:=value`.
+/// Returns `None` if the source doesn't continue with `intro`.
+fn parse_introduced_word(
+    ps: &mut ParserSource,
+    intro: &str,
+    what: &str,
+) -> Result<Option<String>, ParsingError> {
+    ps.push_pos();
+    for ic in intro.chars() {
+        if ps.next() != Some(ic) {
+            ps.pop_pos();
+            return Ok(None);
+        }
+    }
+    ps.drop_pos();
+    match parse_word(ps) {
+        Ok(value) => Ok(Some(value)),
+        Err(ParsingError::Empty) => {
+            Err(ParsingError::Error(format!("{} expected after this", what)))
+        }
+        Err(ParsingError::Error(msg)) => Err(ParsingError::Error(msg)),
+    }
+}
+
+/// Parse the default value of an assignment. Like `:=value`, `:=-5` or `:='a, b'`.
+/// Returns `None` if no default value is given.
+fn parse_default_value(ps: &mut ParserSource) -> Result<Option<String>, ParsingError> {
+    parse_introduced_word(ps, ":=", "default value")
 }
 
 /// Parse a counting option.
-fn parse_counter(ps: &mut ParserSource) -> Result<(OptType, Option<OptAttribute>), ParsingError> {
+fn parse_counter(ps: &mut ParserSource) -> Result<(OptType, Vec<OptAttribute>), ParsingError> {
     match ps.next() {
         Some('+') => (),
         _ => Err(ParsingError::Empty)?,
     }
 
-    let attr = parse_option_attribute(ps);
+    let mut attr = parse_option_attributes(ps);
+    if attr.contains(&OptAttribute::Negatable) {
+        Err(ParsingError::Error(
+            "Negation ('~') not supported for counter".to_string(),
+        ))?;
+    }
 
     let target_name = match parse_name(ps) {
         Ok(name) => name,
@@ -510,6 +1111,8 @@ fn parse_counter(ps: &mut ParserSource) -> Result<(OptType, Option<OptAttribute>
         OptTarget::Variable(target_name)
     };
 
+    attr.append(&mut parse_env_var(ps)?);
+
     Ok((OptType::Counter(target), attr))
 }
 
@@ -517,9 +1120,18 @@ fn parse_counter(ps: &mut ParserSource) -> Result<(OptType, Option<OptAttribute>
 fn parse_opt_def(ps: &mut ParserSource) -> Result<OptConfig, ParsingError> {
     let mut short = String::new();
     let mut long: Vec<String> = Vec::new();
+    let mut numeric = false;
 
     // first options
     loop {
+        // `#` in the option list is the numeric option (`-20`)
+        if ps.next_if(|c| c == '#').is_some() {
+            numeric = true;
+            if ps.next_if(|c| c == ':').is_none() {
+                break;
+            }
+            continue;
+        }
         match parse_option(ps) {
             Ok(o) => {
                 if o.chars().count() == 1 {
@@ -563,14 +1175,52 @@ fn parse_opt_def(ps: &mut ParserSource) -> Result<OptConfig, ParsingError> {
 
     let opt_attr = opt_type.1;
 
+    if numeric && !matches!(opt_type.0, OptType::Assignment(_)) {
+        return Err(ParsingError::Error(
+            "Numeric option ('#') only supported for assignments".to_string(),
+        ));
+    }
+
+    let default_value = match &opt_type.0 {
+        // mode switch marked with `!` is the default mode
+        OptType::ModeSwitch(_, value) if ps.next_if(|c| c == '!').is_some() => Some(value.clone()),
+        OptType::Assignment(_) | OptType::OptionalAssignment(_, _) => parse_default_value(ps)?,
+        _ => None,
+    };
+
+    // assignments might restrict the accepted values
+    let value_restriction = if matches!(
+        opt_type.0,
+        OptType::Assignment(_) | OptType::OptionalAssignment(_, _)
+    ) && ps.next_if(|c| c == '=').is_some()
+    {
+        Some(parse_value_restriction(ps)?)
+    } else {
+        None
+    };
+
     Ok(OptConfig {
         opt_chars: short,
         opt_strings: long,
         opt_type: opt_type.0,
-        required: opt_attr == Some(OptAttribute::Required),
-        singleton: opt_attr == Some(OptAttribute::Singleton),
+        value_restriction,
+        default_value,
+        required: opt_attr.contains(&OptAttribute::Required),
+        singleton: opt_attr.contains(&OptAttribute::Singleton),
+        negatable: opt_attr.contains(&OptAttribute::Negatable),
+        env_var: opt_attr.iter().find_map(|a| match a {
+            OptAttribute::EnvVar(name) => Some(name.clone()),
+            _ => None,
+        }),
+        numeric,
+        separator: opt_attr.into_iter().find_map(|a| match a {
+            OptAttribute::Separator(sep) => Some(sep),
+            _ => None,
+        }),
+        sub_option: false,
         assigned: Cell::new(false),
         count_value: Cell::new(0),
+        plus_given: Cell::new(false),
     })
 }
 
@@ -612,6 +1262,18 @@ pub fn parse(opt_def_str: &str) -> Result<Vec<OptConfig>, String> {
 
 /// Parses a list of option definitions from a ParserSource.
 fn parse_opt_def_list(ps: &mut ParserSource) -> Result<Vec<OptConfig>, ParsingError> {
+    let opt_def_list = parse_opt_def_sequence(ps)?;
+
+    if let Some(c) = ps.next() {
+        Err(ParsingError::Error(format!("Unexpected character '{}'", c)))?
+    }
+
+    Ok(opt_def_list)
+}
+
+/// Parses comma-separated option definitions till the first character that
+/// doesn't continue the list.
+fn parse_opt_def_sequence(ps: &mut ParserSource) -> Result<Vec<OptConfig>, ParsingError> {
     let mut opt_def_list: Vec<OptConfig> = Vec::new();
     loop {
         // eat up consecutive commas
@@ -628,12 +1290,136 @@ fn parse_opt_def_list(ps: &mut ParserSource) -> Result<Vec<OptConfig>, ParsingEr
             break;
         }
     }
+    Ok(opt_def_list)
+}
+
+/// Parses the sub-option definitions after the `{` till the closing `}`.
+/// Only flags and assignments are supported as sub-options.
+fn parse_sub_option_list(ps: &mut ParserSource) -> Result<Vec<OptConfig>, ParsingError> {
+    ps.in_sub_options = true;
+    let result = parse_opt_def_sequence(ps);
+    ps.in_sub_options = false;
+    let mut sub_cfg_list = result?;
+    if ps.next_if(|c| c == '}').is_none() {
+        return Err(ParsingError::Error("'}' expected after this".to_string()));
+    }
+
+    for oc in &mut sub_cfg_list {
+        if !matches!(
+            oc.opt_type,
+            OptType::Flag(_)
+                | OptType::ModeSwitch(_, _)
+                | OptType::Assignment(_)
+                | OptType::OptionalAssignment(_, _)
+        ) {
+            return Err(ParsingError::Error(
+                "Only flags and assignments are supported as sub-options".to_string(),
+            ));
+        }
+        if oc.env_var.is_some() || oc.negatable || oc.singleton || oc.numeric {
+            return Err(ParsingError::Error(
+                "Environment variables, negation, singletons and numeric options are not supported for sub-options"
+                    .to_string(),
+            ));
+        }
+        oc.sub_option = true;
+    }
+    Ok(sub_cfg_list)
+}
+
+/// Parses a single argument definition. Like `src`, `input?`, `files...` or `count=U`.
+fn parse_arg_def(ps: &mut ParserSource) -> Result<ArgConfig, ParsingError> {
+    let name = match parse_name(ps) {
+        Ok(name) => name,
+        Err(ParsingError::Empty) => {
+            if ps.index == 0 {
+                Err(ParsingError::Error("name expected".to_string()))?
+            } else {
+                Err(ParsingError::Error("name expected after this".to_string()))?
+            }
+        }
+        Err(ParsingError::Error(msg)) => Err(ParsingError::Error(msg))?,
+    };
+
+    let optional = ps.next_if(|c| c == '?').is_some();
+
+    ps.push_pos();
+    let variadic = if ps.next() == Some('.') && ps.next() == Some('.') && ps.next() == Some('.') {
+        ps.drop_pos();
+        true
+    } else {
+        ps.pop_pos();
+        false
+    };
+
+    let value_restriction = if ps.next_if(|c| c == '=').is_some() {
+        Some(parse_value_restriction(ps)?)
+    } else {
+        None
+    };
+
+    Ok(ArgConfig {
+        name,
+        optional,
+        variadic,
+        value_restriction,
+    })
+}
+
+/// Parses a list of argument definitions from a ParserSource.
+/// Required arguments must not follow optional arguments and only the
+/// last argument can be variadic.
+fn parse_arg_def_list(ps: &mut ParserSource) -> Result<Vec<ArgConfig>, ParsingError> {
+    let mut arg_def_list: Vec<ArgConfig> = Vec::new();
+    loop {
+        let arg_def = parse_arg_def(ps)?;
+
+        if let Some(prev) = arg_def_list.last() {
+            if prev.variadic {
+                Err(ParsingError::Error(
+                    "Only the last argument can be variadic".to_string(),
+                ))?
+            }
+            if prev.optional && !arg_def.optional {
+                Err(ParsingError::Error(
+                    "Required argument after optional argument".to_string(),
+                ))?
+            }
+        }
+        if arg_def_list.iter().any(|a| a.name == arg_def.name) {
+            Err(ParsingError::Error(format!(
+                "Duplicate argument '{}'",
+                arg_def.name
+            )))?
+        }
+        arg_def_list.push(arg_def);
+
+        if ps.next_if(|c| c == ',').is_none() {
+            break;
+        }
+    }
 
     if let Some(c) = ps.next() {
         Err(ParsingError::Error(format!("Unexpected character '{}'", c)))?
     }
 
-    Ok(opt_def_list)
+    Ok(arg_def_list)
+}
+
+/// Entry function to parse a comma-separated list of argument definitions.
+///
+/// Returns a vector of ArgConfig on success.
+pub fn parse_args(arg_def_str: &str) -> Result<Vec<ArgConfig>, String> {
+    let mut ps = ParserSource::new(arg_def_str);
+    match parse_arg_def_list(&mut ps) {
+        Ok(vec) => Ok(vec),
+        Err(ParsingError::Error(msg)) => Err(format_parsing_error(arg_def_str, ps.index, &msg)),
+        _ => Err(format_parsing_error(
+            arg_def_str,
+            ps.index,
+            &"Can't parse".to_string(),
+        )),
+    }
 }
 
 #[cfg(test)]
@@ -645,10 +1431,18 @@ mod unit_tests {
             opt_chars: String::from("d"),
             opt_strings: vec![String::from("debug")],
             opt_type: OptType::Flag(OptTarget::Variable(String::from("debug"))),
+            value_restriction: None,
+            default_value: None,
             required: false,
             singleton: false,
+            negatable: false,
+            env_var: None,
+            numeric: false,
+            separator: None,
+            sub_option: false,
             assigned: Cell::new(false),
             count_value: Cell::new(0),
+            plus_given: Cell::new(false),
         }
     }
 
@@ -660,10 +1454,18 @@ mod unit_tests {
                 OptTarget::Variable(String::from("mode")),
                 String::from("copy"),
             ),
+            value_restriction: None,
+            default_value: None,
             required: false,
             singleton: false,
+            negatable: false,
+            env_var: None,
+            numeric: false,
+            separator: None,
+            sub_option: false,
             assigned: Cell::new(false),
             count_value: Cell::new(0),
+            plus_given: Cell::new(false),
         }
     }
 
@@ -672,10 +1474,18 @@ mod unit_tests {
             opt_chars: String::from("o"),
             opt_strings: vec![String::from("out-file")],
             opt_type: OptType::Assignment(OptTarget::Variable(String::from("output_file"))),
+            value_restriction: None,
+            default_value: None,
             required: false,
             singleton: false,
+            negatable: false,
+            env_var: None,
+            numeric: false,
+            separator: None,
+            sub_option: false,
             assigned: Cell::new(false),
             count_value: Cell::new(0),
+            plus_given: Cell::new(false),
         }
     }
 
@@ -684,10 +1494,18 @@ mod unit_tests {
             opt_chars: String::from("v"),
             opt_strings: vec![String::from("verbose")],
             opt_type: OptType::Counter(OptTarget::Variable(String::from("verbosity"))),
+            value_restriction: None,
+            default_value: None,
             required: false,
             singleton: false,
+            negatable: false,
+            env_var: None,
+            numeric: false,
+            separator: None,
+            sub_option: false,
             assigned: Cell::new(false),
             count_value: Cell::new(0),
+            plus_given: Cell::new(false),
         }
     }
 
@@ -708,6 +1526,125 @@ mod unit_tests {
         assert_eq!("debug", oc.get_target_name());
     }
 
+    #[test]
+    fn test_opt_config_negatable() {
+        let mut oc = get_od_debug();
+        let no_debug = CmdLineElement::LongOption("no-debug".to_string());
+        assert!(!oc.match_option(&no_debug));
+        assert!(!oc.is_negated_option(&no_debug));
+        assert_eq!("-d/--debug", oc.options_string());
+
+        oc.negatable = true;
+        assert!(oc.match_option(&no_debug));
+        assert!(oc.is_negated_option(&no_debug));
+        assert!(oc.match_option(&CmdLineElement::LongOption("debug".to_string())));
+        assert!(!oc.is_negated_option(&CmdLineElement::LongOption("debug".to_string())));
+        assert!(!oc.match_option(&CmdLineElement::LongOption("no-d".to_string())));
+        assert_eq!("-d/--[no-]debug", oc.options_string());
+    }
+
+    #[test]
+    fn test_opt_config_split_value() {
+        let mut oc = get_od_out_file();
+        assert_eq!(vec!["a,b"], oc.split_value("a,b".to_string()));
+
+        oc.separator = Some(",".to_string());
+        assert_eq!(vec!["a", "b", "c"], oc.split_value("a,b,c".to_string()));
+        assert_eq!(vec!["a", "b"], oc.split_value(",a,,b,".to_string()));
+        assert_eq!(vec!["a,b", "c"], oc.split_value("a\\,b,c".to_string()));
+        assert_eq!(vec!["a\\", "b"], oc.split_value("a\\\\,b".to_string()));
+        assert_eq!(vec!["a\\b"], oc.split_value("a\\b".to_string()));
+        assert_eq!(vec!["a b", "*"], oc.split_value("a b,*".to_string()));
+        assert!(oc.split_value(String::new()).is_empty());
+
+        oc.separator = Some("::".to_string());
+        assert_eq!(vec!["a:b", "c"], oc.split_value("a:b::c".to_string()));
+    }
+
+    #[test]
+    fn test_parse_sub_options() {
+        let mut ps = ParserSource::new("o:options=opts{ro#readonly,u:uid=uid=U},v#verbose");
+        let list = parse_opt_def_list(&mut ps).unwrap();
+        assert_eq!(2, list.len());
+
+        let sub_cfg_list = list[0].get_sub_options().unwrap();
+        assert_eq!(2, sub_cfg_list.len());
+        assert!(sub_cfg_list.iter().all(|oc| oc.sub_option));
+        assert_eq!("ro", sub_cfg_list[0].options_string());
+        assert_eq!("u/uid", sub_cfg_list[1].options_string());
+        assert_eq!(
+            Some(ValueRestriction::Unsigned(None, None)),
+            sub_cfg_list[1].value_restriction
+        );
+        assert_eq!("opts", list[0].get_target_name());
+        assert!(list[1].get_sub_options().is_none());
+
+        // unquoted values end at `}`
+        let mut ps = ParserSource::new("o=opts{x=x:=a,r=r=R=[a-z]{2\\}},v#verbose");
+        let list = parse_opt_def_list(&mut ps).unwrap();
+        assert_eq!(2, list.len());
+        let sub_cfg_list = list[0].get_sub_options().unwrap();
+        assert_eq!(Some("a".to_string()), sub_cfg_list[0].default_value);
+        assert_eq!(
+            Some(ValueRestriction::RegEx("[a-z]{2}".to_string(), false)),
+            sub_cfg_list[1].value_restriction
+        );
+
+        // ... but only within sub-options
+        let mut ps = ParserSource::new("x=x:=a}");
+        assert_eq!(
+            Some("a}".to_string()),
+            parse_opt_def(&mut ps).unwrap().default_value
+        );
+    }
+
+    #[test]
+    fn test_parse_separator() {
+        let mut ps = ParserSource::new("t=@tags/\\,");
+        assert_eq!(
+            Some(",".to_string()),
+            parse_opt_def(&mut ps).unwrap().separator
+        );
+
+        let mut ps = ParserSource::new("t=tags()/;");
+        assert_eq!(
+            Some(";".to_string()),
+            parse_opt_def(&mut ps).unwrap().separator
+        );
+
+        let mut ps = ParserSource::new("t=@tags/'::'");
+        assert_eq!(
+            Some("::".to_string()),
+            parse_opt_def(&mut ps).unwrap().separator
+        );
+    }
+
+    #[test]
+    fn test_opt_config_numeric() {
+        let mut oc = get_od_out_file();
+        let num = CmdLineElement::NumericOption("20".to_string());
+        assert!(!oc.match_option(&num));
+        assert_eq!("-o/--out-file", oc.options_string());
+
+        oc.numeric = true;
+        assert!(oc.match_option(&num));
+        assert_eq!("-o/--out-file/-NUM", oc.options_string());
+    }
+
+    #[test]
+    fn test_parse_opt_def_numeric() {
+        let mut ps = ParserSource::new("n:#:lines=lines");
+        let od = parse_opt_def(&mut ps).unwrap();
+        assert!(od.numeric);
+        assert_eq!("n", od.opt_chars);
+        assert_eq!(vec!["lines".to_string()], od.opt_strings);
+
+        let mut ps = ParserSource::new("#=lines");
+        let od = parse_opt_def(&mut ps).unwrap();
+        assert!(od.numeric);
+        assert_eq!("-NUM", od.options_string());
+    }
+
     #[test]
     fn test_parse_opt_def_flag() {
         let mut ps = ParserSource::new("d:debug#debug");
@@ -750,6 +1687,272 @@ mod unit_tests {
         }
     }
 
+    #[test]
+    fn test_parse_opt_def_assignment_name_set() {
+        let mut ps = ParserSource::new("o:out-file=output_file=L=json|yaml|text");
+
+        match parse_opt_def(&mut ps) {
+            Ok(od) => {
+                let mut expected = get_od_out_file();
+                expected.value_restriction = Some(ValueRestriction::NameSet(
+                    vec!["json".to_string(), "yaml".to_string(), "text".to_string()],
+                    false,
+                ));
+                assert_eq!(expected, od);
+            }
+            Err(err) => {
+                panic!("Parsing failed: {} {:?}", ps.index, err)
+            }
+        }
+
+        let mut ps = ParserSource::new("o:out-file=output_file=Li=json");
+
+        match parse_opt_def(&mut ps) {
+            Ok(od) => {
+                assert_eq!(
+                    Some(ValueRestriction::NameSet(vec!["json".to_string()], true)),
+                    od.value_restriction
+                );
+            }
+            Err(err) => {
+                panic!("Parsing failed: {} {:?}", ps.index, err)
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_opt_def_assignment_regex() {
+        let mut ps = ParserSource::new("o:out-file=output_file=R=[a-z]{1\\,3}\\.txt,d#debug");
+
+        match parse_opt_def(&mut ps) {
+            Ok(od) => {
+                assert_eq!(
+                    Some(ValueRestriction::RegEx(
+                        "[a-z]{1,3}\\.txt".to_string(),
+                        false
+                    )),
+                    od.value_restriction
+                );
+                assert_eq!(Some(','), ps.next());
+            }
+            Err(err) => {
+                panic!("Parsing failed: {} {:?}", ps.index, err)
+            }
+        }
+
+        let mut ps = ParserSource::new("o:out-file=output_file=Ri=^out");
+
+        match parse_opt_def(&mut ps) {
+            Ok(od) => {
+                assert_eq!(
+                    Some(ValueRestriction::RegEx("^out".to_string(), true)),
+                    od.value_restriction
+                );
+            }
+            Err(err) => {
+                panic!("Parsing failed: {} {:?}", ps.index, err)
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_opt_def_assignment_glob() {
+        let mut ps = ParserSource::new("f:file=input=Gi=*.csv");
+
+        match parse_opt_def(&mut ps) {
+            Ok(od) => {
+                assert_eq!(
+                    Some(ValueRestriction::Glob("*.csv".to_string(), true)),
+                    od.value_restriction
+                );
+            }
+            Err(err) => {
+                panic!("Parsing failed: {} {:?}", ps.index, err)
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_value_restriction_numeric() {
+        let mut ps = ParserSource::new("-5<100");
+        assert_eq!(
+            Ok(ValueRestriction::Integer(Some(-5), Some(100))),
+            parse_value_restriction(&mut ps)
+        );
+
+        let mut ps = ParserSource::new("I");
+        assert_eq!(
+            Ok(ValueRestriction::Integer(None, None)),
+            parse_value_restriction(&mut ps)
+        );
+
+        let mut ps = ParserSource::new("I=<0");
+        assert_eq!(
+            Ok(ValueRestriction::Integer(None, Some(0))),
+            parse_value_restriction(&mut ps)
+        );
+
+        let mut ps = ParserSource::new("U=1<65535");
+        assert_eq!(
+            Ok(ValueRestriction::Unsigned(Some(1), Some(65535))),
+            parse_value_restriction(&mut ps)
+        );
+
+        let mut ps = ParserSource::new("F=0.5<");
+        assert_eq!(
+            Ok(ValueRestriction::Float(Some(0.5), None)),
+            parse_value_restriction(&mut ps)
+        );
+
+        let mut ps = ParserSource::new("10<1");
+        assert!(parse_value_restriction(&mut ps).is_err());
+
+        let mut ps = ParserSource::new("U=-1<1");
+        assert!(parse_value_restriction(&mut ps).is_err());
+    }
+
+    #[test]
+    fn test_parse_value() {
+        let mut ps = ParserSource::new("copy,");
+        assert_eq!(Ok("copy".to_string()), parse_value(&mut ps));
+        assert_eq!(Some(','), ps.next());
+
+        let mut ps = ParserSource::new("'release mode',");
+        assert_eq!(Ok("release mode".to_string()), parse_value(&mut ps));
+        assert_eq!(Some(','), ps.next());
+
+        let mut ps = ParserSource::new("\"x86_64-linux\"");
+        assert_eq!(Ok("x86_64-linux".to_string()), parse_value(&mut ps));
+
+        let mut ps = ParserSource::new("'don\\'t \\\\ \\d \"'");
+        assert_eq!(Ok("don't \\ \\d \"".to_string()), parse_value(&mut ps));
+
+        let mut ps = ParserSource::new("''");
+        assert_eq!(Ok("".to_string()), parse_value(&mut ps));
+
+        let mut ps = ParserSource::new("-");
+        assert_eq!(Err(ParsingError::Empty), parse_value(&mut ps));
+
+        let mut ps = ParserSource::new("#x='abc");
+        ps.next();
+        ps.next();
+        ps.next();
+        assert_eq!(
+            Err(ParsingError::Error(
+                "Unterminated quoted string".to_string()
+            )),
+            parse_value(&mut ps)
+        );
+        // points to the character after the opening quote
+        assert_eq!(4, ps.index);
+    }
+
+    #[test]
+    fn test_parse_opt_def_default_value() {
+        let mut ps = ParserSource::new("o:out-file=output_file:='-'");
+
+        match parse_opt_def(&mut ps) {
+            Ok(od) => {
+                let mut expected = get_od_out_file();
+                expected.default_value = Some("-".to_string());
+                assert_eq!(expected, od);
+            }
+            Err(err) => {
+                panic!("Parsing failed: {} {:?}", ps.index, err)
+            }
+        }
+
+        let mut ps = ParserSource::new("o:out-file=output_file:=json=L=json|yaml");
+
+        match parse_opt_def(&mut ps) {
+            Ok(od) => {
+                assert_eq!(Some("json".to_string()), od.default_value);
+                assert!(od.value_restriction.is_some());
+            }
+            Err(err) => {
+                panic!("Parsing failed: {} {:?}", ps.index, err)
+            }
+        }
+
+        let mut ps = ParserSource::new("c:copy#mode=copy!");
+
+        match parse_opt_def(&mut ps) {
+            Ok(od) => {
+                let mut expected = get_od_mode();
+                expected.default_value = Some("copy".to_string());
+                assert_eq!(expected, od);
+            }
+            Err(err) => {
+                panic!("Parsing failed: {} {:?}", ps.index, err)
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_opt_def_optional_assignment() {
+        let mut ps = ParserSource::new("c:color=color?=always:=auto=L=always|never|auto");
+
+        match parse_opt_def(&mut ps) {
+            Ok(od) => {
+                assert_eq!(
+                    OptType::OptionalAssignment(
+                        OptTarget::Variable("color".to_string()),
+                        "always".to_string()
+                    ),
+                    od.opt_type
+                );
+                assert_eq!(Some("auto".to_string()), od.default_value);
+                assert!(od.value_restriction.is_some());
+                assert!(!od.is_duplicate_allowed());
+                assert_eq!("color", od.get_target_name());
+            }
+            Err(err) => {
+                panic!("Parsing failed: {} {:?}", ps.index, err)
+            }
+        }
+
+        let mut ps = ParserSource::new("z:compress=level()?=6");
+
+        match parse_opt_def(&mut ps) {
+            Ok(od) => {
+                assert_eq!(
+                    OptType::OptionalAssignment(
+                        OptTarget::Function("level".to_string()),
+                        "6".to_string()
+                    ),
+                    od.opt_type
+                );
+                assert!(od.is_duplicate_allowed());
+                assert!(od.is_target_function());
+            }
+            Err(err) => {
+                panic!("Parsing failed: {} {:?}", ps.index, err)
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_opt_def_array_assignment() {
+        let mut ps = ParserSource::new("I:include=*@includes");
+
+        match parse_opt_def(&mut ps) {
+            Ok(od) => {
+                assert_eq!(
+                    OptType::Assignment(OptTarget::Array("includes".to_string())),
+                    od.opt_type
+                );
+                assert!(od.required);
+                assert!(od.is_duplicate_allowed());
+                assert!(od.is_target_array());
+                assert!(od.is_target_variable());
+                assert_eq!("includes", od.get_target_name());
+            }
+            Err(err) => {
+                panic!("Parsing failed: {} {:?}", ps.index, err)
+            }
+        }
+    }
+
     #[test]
     fn test_parse_opt_def_counter() {
         let mut ps = ParserSource::new("v:verbose+verbosity");
@@ -819,6 +2022,7 @@ mod unit_tests {
             index: 0,
             length: len,
             position_stack: Vec::new(),
+            in_sub_options: false,
             config: ParserConfig {
                 allow_utf8_options: true,
             },
@@ -900,4 +2104,37 @@ mod unit_tests {
 
         ps.drop_pos();
     }
+
+    #[test]
+    fn test_parse_args() {
+        let args = parse_args("src,dst?,files?...=G=*.txt").unwrap();
+        assert_eq!(
+            vec![
+                ArgConfig {
+                    name: "src".to_string(),
+                    optional: false,
+                    variadic: false,
+                    value_restriction: None,
+                },
+                ArgConfig {
+                    name: "dst".to_string(),
+                    optional: true,
+                    variadic: false,
+                    value_restriction: None,
+                },
+                ArgConfig {
+                    name: "files".to_string(),
+                    optional: true,
+                    variadic: true,
+                    value_restriction: Some(ValueRestriction::Glob("*.txt".to_string(), false)),
+                },
+            ],
+            args
+        );
+
+        assert!(parse_args("files...,dst").is_err());
+        assert!(parse_args("src,src").is_err());
+        assert!(parse_args("src..").is_err());
+        assert!(parse_args("").is_err());
+    }
 }
@@ -0,0 +1,233 @@
+//
+// Part of parseargs - a command line options parser for shell scripts
+//
+// Copyright (c) 2023 Ralf Schandl
+// This code is licensed under MIT license (see LICENSE.txt for details).
+//
+
+//! Expansion of response files given as `@file` script arguments.
+//!
+//! The file contains words separated by whitespace. Quoting is like in the
+//! shell:
+//!
+//! * `'...'` - everything up to the next `'` is taken literally
+//! * `"..."` - a backslash only escapes `"` and `\`
+//! * `\` - outside of quotes escapes the next character, a backslash
+//!   before a newline is removed
+//! * `#` - at the start of a word starts a comment up to the end of the line
+//!
+//! Words starting with `@` within a response file are expanded recursively.
+//! Expansion stops after the argument `--`.
+
+use std::fs;
+
+/// Maximum nesting of response files.
+const MAX_DEPTH: usize = 16;
+
+/// Replaces every argument `@file` with the words read from `file`.
+/// A lone `@` and arguments after `--` are kept as they are.
+pub fn expand_response_files(args: Vec<String>) -> Result<Vec<String>, String> {
+    let mut expanded = vec![];
+    let mut after_separator = false;
+    expand_args(args, 0, &mut after_separator, &mut expanded)?;
+    Ok(expanded)
+}
+
+fn expand_args(
+    args: Vec<String>,
+    depth: usize,
+    after_separator: &mut bool,
+    expanded: &mut Vec<String>,
+) -> Result<(), String> {
+    for arg in args {
+        if *after_separator || arg.len() < 2 || !arg.starts_with('@') {
+            if arg == "--" {
+                *after_separator = true;
+            }
+            expanded.push(arg);
+            continue;
+        }
+
+        let file_name = &arg[1..];
+        if depth >= MAX_DEPTH {
+            return Err(format!("Response files nested too deeply: '{}'", file_name));
+        }
+        let content = match fs::read_to_string(file_name) {
+            Ok(content) => content,
+            Err(e) => return Err(format!("Can't read response file '{}': {}", file_name, e)),
+        };
+        let words = match split_words(&content) {
+            Ok(words) => words,
+            Err(msg) => return Err(format!("{} in response file '{}'", msg, file_name)),
+        };
+        expand_args(words, depth + 1, after_separator, expanded)?;
+    }
+    Ok(())
+}
+
+/// Splits the content of a response file into words.
+fn split_words(content: &str) -> Result<Vec<String>, String> {
+    let mut words = vec![];
+    let mut word = String::new();
+    // Whether a word was started. Needed for empty quoted words like ''.
+    let mut in_word = false;
+
+    let mut chars = content.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("Unterminated single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c) if c == '"' || c == '\\' => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err("Unterminated double quote".to_string()),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err("Unterminated double quote".to_string()),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') => (),
+                Some(c) => {
+                    in_word = true;
+                    word.push(c);
+                }
+                None => return Err("Backslash at end of file".to_string()),
+            },
+            '#' if !in_word => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+
+    Ok(words)
+}
+
+#[cfg(test)]
+mod response_file_tests {
+    use super::{expand_response_files, split_words};
+    use std::fs;
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(split_words(""), Ok(vec![]));
+        assert_eq!(
+            split_words("  -a\t--bbb \n  value\n"),
+            Ok(vec![
+                "-a".to_string(),
+                "--bbb".to_string(),
+                "value".to_string()
+            ])
+        );
+        assert_eq!(
+            split_words("'a b' \"c \\\"d\\\" \\x\" e\\ f ''"),
+            Ok(vec![
+                "a b".to_string(),
+                "c \"d\" \\x".to_string(),
+                "e f".to_string(),
+                "".to_string()
+            ])
+        );
+        assert_eq!(
+            split_words("# comment\n-a # comment\nx#y '#z'\n"),
+            Ok(vec!["-a".to_string(), "x#y".to_string(), "#z".to_string()])
+        );
+        assert_eq!(split_words("a\\\nb"), Ok(vec!["ab".to_string()]));
+        assert_eq!(
+            split_words("'abc"),
+            Err("Unterminated single quote".to_string())
+        );
+        assert_eq!(
+            split_words("\"abc\\\""),
+            Err("Unterminated double quote".to_string())
+        );
+        assert_eq!(
+            split_words("abc\\"),
+            Err("Backslash at end of file".to_string())
+        );
+    }
+
+    #[test]
+    fn test_expand_response_files() {
+        let dir = std::env::temp_dir().join(format!("parseargs-rsp-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let outer = dir.join("outer.txt");
+        let inner = dir.join("inner.txt");
+        let looping = dir.join("loop.txt");
+        fs::write(&inner, "-b 'two words'\n").unwrap();
+        fs::write(&outer, format!("-a @{}\n-c", inner.display())).unwrap();
+        fs::write(&looping, format!("@{}", looping.display())).unwrap();
+
+        let args = vec![
+            "-x".to_string(),
+            format!("@{}", outer.display()),
+            "@".to_string(),
+            "--".to_string(),
+            format!("@{}", outer.display()),
+        ];
+        assert_eq!(
+            expand_response_files(args),
+            Ok(vec![
+                "-x".to_string(),
+                "-a".to_string(),
+                "-b".to_string(),
+                "two words".to_string(),
+                "-c".to_string(),
+                "@".to_string(),
+                "--".to_string(),
+                format!("@{}", outer.display()),
+            ])
+        );
+
+        assert_eq!(
+            expand_response_files(vec![format!("@{}", looping.display())]),
+            Err(format!(
+                "Response files nested too deeply: '{}'",
+                looping.display()
+            ))
+        );
+
+        let missing = dir.join("missing.txt");
+        let result = expand_response_files(vec![format!("@{}", missing.display())]);
+        assert!(result.unwrap_err().starts_with(&format!(
+            "Can't read response file '{}': ",
+            missing.display()
+        )));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
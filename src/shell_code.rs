@@ -17,7 +17,7 @@ const SHELL_EXIT: &str = "exit";
 /// or given as argument in a function call.
 pub enum VarValue {
     StringValue(String),
-    IntValue(i32),
+    IntValue(i64),
     BoolValue(bool),
     None,
 }
@@ -75,8 +75,16 @@ pub enum CodeChunk {
     AssignEmptyArray(String),
     AddToArray(String, VarValue),
 
+    /// Declare an associative array.
+    DeclareMapVar(String),
+    AssignEmptyMap(String),
+    /// Add a key/value pair to an associative array.
+    AddToMap(String, String, VarValue),
+
     CheckForFunction(String),
     CallFunction(String, VarValue),
+    /// Call a function with multiple arguments.
+    CallFunctionWithArgs(String, Vec<String>),
 
     /// Set the positional parameter `$1`, `$2` ...
     SetArgs(Vec<String>),
@@ -95,6 +103,8 @@ pub enum CodeChunk {
 pub struct CodeTemplates {
     /// Whether the shell supports arrays.
     pub supports_arrays: bool,
+    /// Whether the shell supports associative arrays.
+    pub supports_maps: bool,
 
     /// Seperator between statements. Most likely always `;`.
     statement_separator: &'static str,
@@ -106,9 +116,14 @@ pub struct CodeTemplates {
     assign_empty_array: &'static str,
     add_to_array: &'static str,
 
+    declare_map_variable: &'static str,
+    assign_empty_map: &'static str,
+    add_to_map: &'static str,
+
     /// Code to check whether a function exists. Exits the script if not found.
     check_function_exists: &'static str,
     call_function: &'static str,
+    call_function_with_args: &'static str,
     exit: &'static str,
 
     false_return: &'static str,
@@ -163,12 +178,37 @@ impl CodeTemplates {
                 }
                 self.format_code_name_value(self.add_to_array, name, value)
             }
+            CodeChunk::DeclareMapVar(name) => {
+                // Should be checked before whether this is supported.
+                if !self.supports_maps {
+                    panic!("Associative arrays not supported")
+                }
+                self.format_code_name(self.declare_map_variable, name)
+            }
+            CodeChunk::AssignEmptyMap(name) => {
+                // Should be checked before whether this is supported.
+                if !self.supports_maps {
+                    panic!("Associative arrays not supported")
+                }
+                self.format_code_name(self.assign_empty_map, name)
+            }
+            CodeChunk::AddToMap(name, key, value) => {
+                // Should be checked before whether this is supported.
+                if !self.supports_maps {
+                    panic!("Associative arrays not supported")
+                }
+                self.format_code_name_key_value(self.add_to_map, name, key, value)
+            }
             CodeChunk::CheckForFunction(name) => {
                 self.format_code_name(self.check_function_exists, name)
             }
             CodeChunk::CallFunction(name, value) => {
                 self.format_code_name_value(self.call_function, name, value)
             }
+            CodeChunk::CallFunctionWithArgs(name, args) => self.format_code_args(
+                &self.format_code_name(self.call_function_with_args, name),
+                args,
+            ),
             CodeChunk::Exit(exit_value) => self.format_code_int_value(self.exit, *exit_value),
             CodeChunk::FalseReturn => self.false_return.to_string(),
             CodeChunk::SetArgs(args) => self.format_code_args(self.set_args, args),
@@ -185,6 +225,41 @@ impl CodeTemplates {
         tmpl.replace("{NAME}", name)
             .replace("{VALUE}", &value.to_string())
     }
+    /// Format code with the replacement marker `{NAME}`, `{KEY}` and `{VALUE}`.
+    /// The markers are replaced in a single pass, as key and value are
+    /// user input that might contain a marker.
+    fn format_code_name_key_value(
+        &self,
+        tmpl: &str,
+        name: &str,
+        key: &str,
+        value: &VarValue,
+    ) -> String {
+        let replacements = [
+            ("{NAME}", name.to_string()),
+            ("{KEY}", VarValue::escape_string(key)),
+            ("{VALUE}", value.to_string()),
+        ];
+        let mut code = String::new();
+        let mut rest = tmpl;
+        while !rest.is_empty() {
+            match replacements
+                .iter()
+                .find(|(marker, _)| rest.starts_with(marker))
+            {
+                Some((marker, replacement)) => {
+                    code.push_str(replacement);
+                    rest = &rest[marker.len()..];
+                }
+                None => {
+                    let c = rest.chars().next().unwrap();
+                    code.push(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+        code
+    }
     /// Format code with the replacement marker `{NAME}` and `{VALUE}`.
     fn format_code_int_value(&self, tmpl: &str, value: i32) -> String {
         tmpl.replace("{VALUE}", &value.to_string())
@@ -211,6 +286,7 @@ impl CodeTemplates {
 ///
 const SH_TEMPLATE : CodeTemplates = CodeTemplates {
     supports_arrays : false,
+    supports_maps : false,
 
     statement_separator : ";\n",
 
@@ -221,8 +297,13 @@ const SH_TEMPLATE : CodeTemplates = CodeTemplates {
     assign_empty_array : "",
     add_to_array : "",
 
+    declare_map_variable: "",
+    assign_empty_map : "",
+    add_to_map : "",
+
     check_function_exists : "if ! LC_ALL=C command -V {NAME} 2>/dev/null | head -n1 | grep function >/dev/null; then echo >&2 \"ERROR: Function '{NAME}' does not exist.\"; exit 127; fi" ,
     call_function : "{NAME} {VALUE} || exit $?",
+    call_function_with_args : "{NAME} {ARGS} || exit $?",
 
     set_args : "set -- {ARGS}",
     exit : "exit {VALUE}",
@@ -235,12 +316,17 @@ const SH_TEMPLATE : CodeTemplates = CodeTemplates {
 ///
 const BASH_TEMPLATE : CodeTemplates = CodeTemplates {
     supports_arrays : true,
+    supports_maps : true,
 
     declare_array_variable: "typeset -a {NAME}",
 
     assign_empty_array : "{NAME}=()",
     add_to_array : "{NAME}+=({VALUE})",
 
+    declare_map_variable: "typeset -A {NAME}",
+    assign_empty_map : "{NAME}=()",
+    add_to_map : "{NAME}[{KEY}]={VALUE}",
+
     check_function_exists : "if ! typeset -f {NAME} >/dev/null 2>&1; then echo >&2 \"ERROR: Function '{NAME}' does not exist.\"; exit 127; fi" ,
 
     // others from sh template
@@ -252,6 +338,7 @@ const BASH_TEMPLATE : CodeTemplates = CodeTemplates {
 ///
 const KSH_TEMPLATE: CodeTemplates = CodeTemplates {
     assign_empty_array: "set -A {NAME}",
+    assign_empty_map: "unset {NAME}; typeset -A {NAME}",
 
     // others from the bash template
     ..BASH_TEMPLATE
@@ -400,6 +487,12 @@ mod shell_template_test {
             CodeChunk::CallFunction(var_name.clone(), VarValue::StringValue("value".to_string()));
         assert_eq!("func 'value' || exit $?", shell.format(&chunk));
 
+        let chunk = CodeChunk::CallFunctionWithArgs(
+            var_name.clone(),
+            vec!["ls".to_string(), "don't".to_string()],
+        );
+        assert_eq!("func 'ls' 'don'\\''t' || exit $?", shell.format(&chunk));
+
         let chunk = CodeChunk::CheckForFunction(var_name.clone());
         assert_eq!("if ! LC_ALL=C command -V func 2>/dev/null | head -n1 | grep function >/dev/null; then echo >&2 \"ERROR: Function 'func' does not exist.\"; exit 127; fi", shell.format(&chunk));
 
@@ -425,7 +518,22 @@ mod shell_template_test {
         let chunk = CodeChunk::AssignEmptyArray(var_name.clone());
         assert!(std::panic::catch_unwind(|| shell.format(&chunk)).is_err());
 
-        let chunk = CodeChunk::AddToArray(var_name, VarValue::StringValue("test".to_string()));
+        let chunk =
+            CodeChunk::AddToArray(var_name.clone(), VarValue::StringValue("test".to_string()));
+        assert!(std::panic::catch_unwind(|| shell.format(&chunk)).is_err());
+
+        // associative arrays are not supported
+        let chunk = CodeChunk::DeclareMapVar(var_name.clone());
+        assert!(std::panic::catch_unwind(|| shell.format(&chunk)).is_err());
+
+        let chunk = CodeChunk::AssignEmptyMap(var_name.clone());
+        assert!(std::panic::catch_unwind(|| shell.format(&chunk)).is_err());
+
+        let chunk = CodeChunk::AddToMap(
+            var_name,
+            "key".to_string(),
+            VarValue::StringValue("test".to_string()),
+        );
         assert!(std::panic::catch_unwind(|| shell.format(&chunk)).is_err());
     }
 
@@ -454,9 +562,31 @@ mod shell_template_test {
         let chunk = CodeChunk::AssignEmptyArray(var_name.clone());
         assert_eq!("name=()", shell.format(&chunk));
 
-        let chunk = CodeChunk::AddToArray(var_name, VarValue::StringValue("test".to_string()));
+        let chunk =
+            CodeChunk::AddToArray(var_name.clone(), VarValue::StringValue("test".to_string()));
         assert_eq!("name+=('test')", shell.format(&chunk));
 
+        let chunk = CodeChunk::DeclareMapVar(var_name.clone());
+        assert_eq!("typeset -A name", shell.format(&chunk));
+
+        let chunk = CodeChunk::AssignEmptyMap(var_name.clone());
+        assert_eq!("name=()", shell.format(&chunk));
+
+        let chunk = CodeChunk::AddToMap(
+            var_name.clone(),
+            "don't".to_string(),
+            VarValue::StringValue("test".to_string()),
+        );
+        assert_eq!("name['don'\\''t']='test'", shell.format(&chunk));
+
+        // markers in key and value are not replaced
+        let chunk = CodeChunk::AddToMap(
+            var_name.clone(),
+            "{VALUE}{NAME}".to_string(),
+            VarValue::StringValue("{KEY}{NAME}".to_string()),
+        );
+        assert_eq!("name['{VALUE}{NAME}']='{KEY}{NAME}'", shell.format(&chunk));
+
         let var_name = "func".to_string();
 
         let chunk =
@@ -505,9 +635,23 @@ mod shell_template_test {
         let chunk = CodeChunk::AssignEmptyArray(var_name.clone());
         assert_eq!("set -A name", shell.format(&chunk));
 
-        let chunk = CodeChunk::AddToArray(var_name, VarValue::StringValue("test".to_string()));
+        let chunk =
+            CodeChunk::AddToArray(var_name.clone(), VarValue::StringValue("test".to_string()));
         assert_eq!("name+=('test')", shell.format(&chunk));
 
+        let chunk = CodeChunk::DeclareMapVar(var_name.clone());
+        assert_eq!("typeset -A name", shell.format(&chunk));
+
+        let chunk = CodeChunk::AssignEmptyMap(var_name.clone());
+        assert_eq!("unset name; typeset -A name", shell.format(&chunk));
+
+        let chunk = CodeChunk::AddToMap(
+            var_name.clone(),
+            "don't".to_string(),
+            VarValue::StringValue("test".to_string()),
+        );
+        assert_eq!("name['don'\\''t']='test'", shell.format(&chunk));
+
         let var_name = "func".to_string();
 
         let chunk =
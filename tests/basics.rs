@@ -37,12 +37,12 @@ fn test_version() {
 #[test]
 fn test_unknown_option() {
     parseargs()
-        .arg("--unknown")
+        .arg("--unsupported")
         .assert()
         .code(11)
         .stdout("exit 1\n")
         .stderr(predicate::str::contains(
-            "error: unexpected argument '--unknown' found",
+            "error: unexpected argument '--unsupported' found",
         ));
 }
 
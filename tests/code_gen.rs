@@ -263,6 +263,39 @@ fn test_mode_switch_option() {
     );
 }
 
+#[test]
+fn test_mode_switch_quoted_value() {
+    exec::test_code_gen(
+        &[
+            "-o",
+            "r:release#build='release mode',d:debug#build=\"debug mode\"",
+        ],
+        &["-r"],
+        &["build='release mode';", "set --"],
+    );
+
+    exec::test_code_gen(
+        &[
+            "-o",
+            "l:linux#target=\"x86_64-linux\",w:windows#target='x86_64-w64/mingw'",
+        ],
+        &["--windows"],
+        &["target='x86_64-w64/mingw';", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-o", "q:quote#value='don\\'t',c:comma#value=','"],
+        &["-q"],
+        &["value='don'\\''t';", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-o", "q:quote#value='don\\'t',c:comma#value=','"],
+        &["-c"],
+        &["value=',';", "set --"],
+    );
+}
+
 #[test]
 fn test_combined_options() {
     let expected = &[
@@ -475,3 +508,1508 @@ fn test_multiple_o_options() {
         &["debug='true';", "long='true';", "comma='true';", "set --"],
     );
 }
+
+#[test]
+fn test_name_set_restriction() {
+    exec::test_code_gen(
+        &["-o", "f:format=format=L=json|yaml|text"],
+        &["-f", "yaml"],
+        &["format='yaml';", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-o", "f:format=format=L=json|yaml|text"],
+        &["--format=text"],
+        &["format='text';", "set --"],
+    );
+
+    // case-insensitive assigns the name as defined
+    exec::test_code_gen(
+        &["-o", "f:format=format=Li=json|yaml|text"],
+        &["--format=YAML"],
+        &["format='yaml';", "set --"],
+    );
+
+    exec::test_error_msg(
+        &["-o", "f:format=format=L=json|yaml|text"],
+        &["--format=YAML"],
+        "parseargs: Invalid value for -f/--format: 'YAML' (allowed: json, yaml, text)",
+    );
+
+    exec::test_error_msg(
+        &["-o", "f:format=format()=L=json|yaml|text"],
+        &["-f", "xml"],
+        "parseargs: Invalid value for -f/--format: 'xml' (allowed: json, yaml, text)",
+    );
+}
+
+#[test]
+fn test_regex_restriction() {
+    exec::test_code_gen(
+        &["-o", "t:ticket=ticket=R=[A-Z]+-[0-9]+"],
+        &["-t", "PA-123"],
+        &["ticket='PA-123';", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-o", "t:ticket=ticket=Ri=[A-Z]+-[0-9]+"],
+        &["--ticket=pa-123"],
+        &["ticket='pa-123';", "set --"],
+    );
+
+    // comma in pattern needs to be escaped or the pattern quoted
+    exec::test_code_gen(
+        &[
+            "-o",
+            "v:version=version=R='[0-9]{1,3}(\\.[0-9]+){2}',d#debug",
+        ],
+        &["-d", "--version=1.20.3"],
+        &["debug='true';", "version='1.20.3';", "set --"],
+    );
+    exec::test_code_gen(
+        &[
+            "-o",
+            "v:version=version=R=[0-9]{1\\,3}(\\.[0-9]+){2},d#debug",
+        ],
+        &["-d", "--version=1.20.3"],
+        &["debug='true';", "version='1.20.3';", "set --"],
+    );
+
+    // the whole value has to match
+    exec::test_error_msg(
+        &["-o", "t:ticket=ticket=R=[A-Z]+-[0-9]+"],
+        &["-t", "see PA-123"],
+        "parseargs: Invalid value for -t/--ticket: 'see PA-123' (must match regex '[A-Z]+-[0-9]+')",
+    );
+
+    exec::test_error_msg(
+        &["-o", "t:ticket=ticket=R=[A-Z]+-[0-9]+"],
+        &["-t", "pa-123"],
+        "parseargs: Invalid value for -t/--ticket: 'pa-123' (must match regex '[A-Z]+-[0-9]+')",
+    );
+}
+
+#[test]
+fn test_glob_restriction() {
+    exec::test_code_gen(
+        &["-o", "f:file=input=G=*.csv"],
+        &["-f", "data.csv"],
+        &["input='data.csv';", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-o", "f:file=input=Gi=*.csv"],
+        &["--file=DATA.CSV"],
+        &["input='DATA.CSV';", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-o", "H:host=host=G=[a-z]*[0-9].example.com"],
+        &["-H", "web01.example.com"],
+        &["host='web01.example.com';", "set --"],
+    );
+
+    exec::test_error_msg(
+        &["-o", "f:file=input=G=*.csv"],
+        &["-f", "data.txt"],
+        "parseargs: Invalid value for -f/--file: 'data.txt' (must match pattern '*.csv')",
+    );
+
+    // error callback is called
+    exec::test_parseargs(
+        &["-e", "error_cb", "-o", "f:file=input=G=*.csv"],
+        &["-f", "DATA.CSV"],
+        1,
+        &["error_cb  || exit $?;", "exit 1"],
+        &["parseargs: Invalid value for -f/--file: 'DATA.CSV' (must match pattern '*.csv')"],
+    );
+}
+
+#[test]
+fn test_numeric_restriction() {
+    exec::test_code_gen(
+        &["-o", "r:retries=retries=0<10"],
+        &["-r", "3"],
+        &["retries=3;", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-o", "o:offset=offset=I"],
+        &["--offset=-42"],
+        &["offset=-42;", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-o", "p:port=port=U=1<65535"],
+        &["--port", "8080"],
+        &["port=8080;", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-o", "t:timeout=timeout=F=0<"],
+        &["--timeout", "2.5"],
+        &["timeout='2.5';", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-o", "r:retries=retries()=0<10"],
+        &["-r", "10"],
+        &[
+            "if ! LC_ALL=C command -V retries 2>/dev/null | head -n1 | grep function >/dev/null; then echo >&2 \"ERROR: Function 'retries' does not exist.\"; exit 127; fi;",
+            "retries 10 || exit $?;",
+            "set --",
+        ],
+    );
+
+    exec::test_error_msg(
+        &["-o", "r:retries=retries=0<10"],
+        &["-r", "11"],
+        "parseargs: Value out of range for -r/--retries: '11' (allowed: 0 - 10)",
+    );
+
+    exec::test_error_msg(
+        &["-o", "r:retries=retries=0<10"],
+        &["-r", "three"],
+        "parseargs: Invalid integer value for -r/--retries: 'three'",
+    );
+
+    exec::test_error_msg(
+        &["-o", "p:port=port=U=1<65535"],
+        &["-p", "-1"],
+        "parseargs: Invalid unsigned integer value for -p/--port: '-1'",
+    );
+
+    exec::test_error_msg(
+        &["-o", "p:port=port=U=1<"],
+        &["-p", "0"],
+        "parseargs: Value out of range for -p/--port: '0' (allowed: >= 1)",
+    );
+
+    exec::test_error_msg(
+        &["-o", "t:timeout=timeout=F=<60"],
+        &["-t", "60.5"],
+        "parseargs: Value out of range for -t/--timeout: '60.5' (allowed: <= 60)",
+    );
+
+    exec::test_error_msg(
+        &["-o", "t:timeout=timeout=F"],
+        &["-t", "inf"],
+        "parseargs: Invalid float value for -t/--timeout: 'inf'",
+    );
+}
+
+#[test]
+fn test_default_values() {
+    exec::test_code_gen(
+        &["-o", "o:out=output:='-'"],
+        &[],
+        &["output='-';", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-o", "o:out=output:='-'"],
+        &["-o", "result.txt"],
+        &["output='-';", "output='result.txt';", "set --"],
+    );
+
+    // default is also used with --init-vars
+    exec::test_code_gen(
+        &["-i", "-o", "d#debug,o:out=output:='-'"],
+        &[],
+        &["debug='';", "output='-';", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-o", "o:out=output:=/tmp/out.txt,d#debug"],
+        &["-d"],
+        &["output='/tmp/out.txt';", "debug='true';", "set --"],
+    );
+
+    // unquoted default value with colon
+    exec::test_code_gen(
+        &["-o", "u:url=url:=http://localhost:8080,d#debug"],
+        &[],
+        &["url='http://localhost:8080';", "set --"],
+    );
+
+    // default is converted like a given value
+    exec::test_code_gen(
+        &["-o", "r:retries=retries:=3=0<10"],
+        &[],
+        &["retries=3;", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-o", "c:copy#mode=copy,m:move#mode=move!"],
+        &[],
+        &["mode='move';", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-i", "-o", "c:copy#mode=copy,m:move#mode=move!"],
+        &["--copy"],
+        &["mode='move';", "mode='copy';", "set --"],
+    );
+}
+
+#[test]
+fn test_optional_argument() {
+    exec::test_code_gen(
+        &["-o", "c:color=color?=always"],
+        &["--color"],
+        &["color='always';", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-o", "c:color=color?=always"],
+        &["--color=never"],
+        &["color='never';", "set --"],
+    );
+
+    // separate argument is not consumed
+    exec::test_code_gen(
+        &["-o", "c:color=color?=always"],
+        &["--color", "never"],
+        &["color='always';", "set -- 'never'"],
+    );
+
+    exec::test_code_gen(
+        &["-o", "c:color=color?=always"],
+        &["-c", "never"],
+        &["color='always';", "set -- 'never'"],
+    );
+
+    exec::test_code_gen(
+        &["-o", "c:color=color?=always"],
+        &["-cnever"],
+        &["color='never';", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-o", "c:color=color?=always,d#debug"],
+        &["-dc"],
+        &["debug='true';", "color='always';", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-o", "c:color=color?=always:=auto=L=always|never|auto"],
+        &[],
+        &["color='auto';", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-o", "p:proxy=proxy?=http://proxy:3128:=http://direct"],
+        &["--proxy"],
+        &[
+            "proxy='http://direct';",
+            "proxy='http://proxy:3128';",
+            "set --",
+        ],
+    );
+
+    exec::test_code_gen(
+        &["-o", "z:compress=level?=6=1<9"],
+        &["-z"],
+        &["level=6;", "set --"],
+    );
+
+    exec::test_error_msg(
+        &["-o", "c:color=color?=always=L=always|never|auto"],
+        &["--color=sometimes"],
+        "parseargs: Invalid value for -c/--color: 'sometimes' (allowed: always, never, auto)",
+    );
+}
+
+#[test]
+fn test_array_assignment() {
+    exec::test_parseargs_error_msg(
+        &["-o", "I:include=@includes"],
+        "parseargs: Shell sh does not support arrays, so array option -I/--include is not supported",
+    );
+
+    exec::test_code_gen(
+        &["-sbash", "-o", "I:include=@includes"],
+        &[],
+        &["typeset -a includes;", "includes=();", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-sbash", "-o", "I:include=@includes,d#debug"],
+        &["-I", "/usr/include", "-d", "--include=src", "-Ilib"],
+        &[
+            "typeset -a includes;",
+            "includes=();",
+            "includes+=('/usr/include');",
+            "debug='true';",
+            "includes+=('src');",
+            "includes+=('lib');",
+            "set --",
+        ],
+    );
+
+    exec::test_code_gen(
+        &["-sksh", "-o", "t:tag=@tags=L=alpha|beta"],
+        &["-t", "beta", "-t", "alpha"],
+        &[
+            "typeset -a tags;",
+            "set -A tags;",
+            "tags+=('beta');",
+            "tags+=('alpha');",
+            "set --",
+        ],
+    );
+
+    exec::test_error_msg(
+        &["-szsh", "-o", "I:include=*@includes"],
+        &[],
+        "parseargs: Required option not found: -I/--include",
+    );
+}
+
+#[test]
+fn test_negated_options() {
+    exec::test_code_gen(
+        &["-o", "c:color#~color,o:output=~out_file"],
+        &["--no-color", "--no-output"],
+        &["color='';", "out_file='';", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-o", "c:color#~color,o:output=~out_file:=out.txt"],
+        &["--color", "--no-output"],
+        &[
+            "out_file='out.txt';",
+            "color='true';",
+            "out_file='';",
+            "set --",
+        ],
+    );
+
+    exec::test_code_gen(
+        &["-o", "color:colour#*~color"],
+        &["--no-colour"],
+        &["color='';", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-sbash", "-o", "c:color#~set_color()"],
+        &["--color", "--no-color"],
+        &[
+            "if ! typeset -f set_color >/dev/null 2>&1; then echo >&2 \"ERROR: Function 'set_color' does not exist.\"; exit 127; fi;",
+            "set_color 'true' || exit $?;",
+            "set_color '' || exit $?;",
+            "set --",
+        ],
+    );
+
+    exec::test_code_gen(
+        &["-sbash", "-o", "I:include=~@includes"],
+        &["-Ia", "--no-include", "-Ib"],
+        &[
+            "typeset -a includes;",
+            "includes=();",
+            "includes+=('a');",
+            "includes=();",
+            "includes+=('b');",
+            "set --",
+        ],
+    );
+
+    exec::test_error_msg(
+        &["-o", "c:color#~color"],
+        &["--color", "--no-color"],
+        "parseargs: Duplicate option: --no-color (-c/--[no-]color)",
+    );
+
+    exec::test_error_msg(
+        &["-o", "c:color#~color"],
+        &["--no-color=yes"],
+        "parseargs: -c/--[no-]color: No value supported.",
+    );
+
+    exec::test_error_msg(
+        &["-o", "c:color#~color"],
+        &["--no-c"],
+        "parseargs: Unknown option: --no-c",
+    );
+}
+
+#[test]
+fn test_env_var_fallback() {
+    exec::test_parseargs_env(
+        &[("PA_TEST_TOKEN", "won't tell")],
+        &["-o", "t:token=token@PA_TEST_TOKEN"],
+        &[],
+        0,
+        &["token='won'\\''t tell';", "set --"],
+        &[],
+    );
+
+    // command line has precedence
+    exec::test_parseargs_env(
+        &[("PA_TEST_TOKEN", "env")],
+        &["-o", "t:token=*token@PA_TEST_TOKEN"],
+        &["-t", "cli"],
+        0,
+        &["token='cli';", "set --"],
+        &[],
+    );
+
+    // empty value is ignored
+    exec::test_parseargs_env(
+        &[("PA_TEST_TOKEN", "")],
+        &["-o", "t:token=*token@PA_TEST_TOKEN"],
+        &[],
+        1,
+        &["exit 1"],
+        &["parseargs: Required option not found: -t/--token"],
+    );
+
+    exec::test_parseargs_env(
+        &[("PA_TEST_DEBUG", "yes"), ("PA_TEST_VERBOSE", "3")],
+        &["-o", "d#debug@PA_TEST_DEBUG,v+verbosity@PA_TEST_VERBOSE"],
+        &[],
+        0,
+        &["verbosity=0;", "debug='true';", "verbosity=3;", "set --"],
+        &[],
+    );
+
+    exec::test_parseargs_env(
+        &[("PA_TEST_LEVEL", "11")],
+        &["-o", "l:level=level@PA_TEST_LEVEL=1<10"],
+        &[],
+        1,
+        &["exit 1"],
+        &["parseargs: Value out of range for -l/--level: '11' (allowed: 1 - 10) (from environment variable PA_TEST_LEVEL)"],
+    );
+
+    exec::test_parseargs_env(
+        &[
+            ("MYAPP_DRY_RUN", "true"),
+            ("MYAPP_OUTPUT", "out.txt"),
+            ("MYAPP_MODE", "copy"),
+            ("OTHER_FILE", "other.txt"),
+        ],
+        &[
+            "--env-prefix",
+            "MYAPP_",
+            "-o",
+            "n:dry-run#dry_run,o:output=output,c:mode#mode=copy,f:file=file@OTHER_FILE",
+        ],
+        &[],
+        0,
+        &[
+            "dry_run='true';",
+            "output='out.txt';",
+            "file='other.txt';",
+            "set --",
+        ],
+        &[],
+    );
+}
+
+#[test]
+fn test_subcommands() {
+    let pa_args = [
+        "-o",
+        "v+verbosity",
+        "--subcommand",
+        "build:r:release#release",
+        "--subcommand",
+        "deploy:e:env=*env",
+        "--subcommand",
+        "status",
+    ];
+
+    exec::test_code_gen(
+        &pa_args,
+        &["-v", "build", "-r", "target"],
+        &[
+            "verbosity=0;",
+            "verbosity=1;",
+            "subcommand='build';",
+            "release='true';",
+            "set -- 'target'",
+        ],
+    );
+
+    exec::test_code_gen(
+        &pa_args,
+        &["deploy", "target", "--env", "prod"],
+        &[
+            "verbosity=0;",
+            "subcommand='deploy';",
+            "env='prod';",
+            "set -- 'target'",
+        ],
+    );
+
+    exec::test_code_gen(
+        &[
+            "-i",
+            "--subcommand-var",
+            "cmd",
+            "--subcommand",
+            "status:l#long",
+        ],
+        &["status"],
+        &["long='';", "cmd='status';", "set --"],
+    );
+
+    exec::test_error_msg(
+        &pa_args,
+        &["-v"],
+        "parseargs: Missing subcommand (one of: build, deploy, status)",
+    );
+    exec::test_error_msg(&pa_args, &["clean"], "parseargs: Unknown subcommand: clean");
+    exec::test_error_msg(&pa_args, &["-r", "build"], "parseargs: Unknown option: -r");
+    // global options are not supported after the subcommand
+    exec::test_error_msg(&pa_args, &["build", "-v"], "parseargs: Unknown option: -v");
+    exec::test_error_msg(
+        &pa_args,
+        &["deploy"],
+        "parseargs: Required option not found: -e/--env",
+    );
+
+    exec::test_code_gen(
+        &["-sbash", "-h", "--subcommand", "build:r#release"],
+        &["build", "-r", "--help"],
+        &[
+            "if ! typeset -f show_help >/dev/null 2>&1; then echo >&2 \"ERROR: Function 'show_help' does not exist.\"; exit 127; fi;",
+            "subcommand='build';",
+            "show_help  || exit $?;",
+            "exit 0",
+        ],
+    );
+
+    exec::test_parseargs_error_msg(
+        &["--subcommand", "build:x#", "--subcommand", "build"],
+        "parseargs: Error parsing option definition of subcommand 'build':\nx#\n ^\n name expected after this",
+    );
+    exec::test_parseargs_error_msg(
+        &["--subcommand", "build", "--subcommand", "build"],
+        "parseargs: Duplicate definition of subcommand 'build'",
+    );
+    exec::test_parseargs_error_msg(
+        &["--subcommand=-build"],
+        "parseargs: Invalid subcommand name '-build'",
+    );
+    exec::test_parseargs_error_msg(
+        &["-o", "v#verbose", "--subcommand", "build:v#debug"],
+        "parseargs: Duplicate definition of option '-v'",
+    );
+    exec::test_parseargs_error_msg(
+        &["--subcommand", "build:s#subcommand"],
+        "parseargs: Duplicate usage of variable/function 'subcommand'",
+    );
+}
+
+#[test]
+fn test_named_arguments() {
+    exec::test_code_gen(
+        &["-o", "d#debug", "--args", "src,dst"],
+        &["a.txt", "-d", "b.txt"],
+        &["debug='true';", "src='a.txt';", "dst='b.txt';", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-i", "--args", "input?,output?"],
+        &["in.txt"],
+        &["input='';", "output='';", "input='in.txt';", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-sbash", "--args", "dst,files..."],
+        &["dir", "a", "b"],
+        &[
+            "typeset -a files;",
+            "files=();",
+            "dst='dir';",
+            "files+=('a');",
+            "files+=('b');",
+            "set --",
+        ],
+    );
+
+    exec::test_code_gen(
+        &["-sbash", "--args", "files?..."],
+        &[],
+        &["typeset -a files;", "files=();", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["--args", "count=1<10,mode=L=fast|slow"],
+        &["3", "fast"],
+        &["count=3;", "mode='fast';", "set --"],
+    );
+
+    exec::test_error_msg(
+        &["--args", "src,dst"],
+        &["a"],
+        "parseargs: Missing argument: DST",
+    );
+    exec::test_error_msg(
+        &["-sbash", "--args", "dst,files..."],
+        &["dir"],
+        "parseargs: Missing argument: FILES",
+    );
+    exec::test_error_msg(
+        &["--args", "src,dst"],
+        &["a", "b", "c"],
+        "parseargs: Unexpected argument: 'c'",
+    );
+    exec::test_error_msg(
+        &["--args", "count=1<10"],
+        &["11"],
+        "parseargs: Value out of range for COUNT: '11' (allowed: 1 - 10)",
+    );
+
+    exec::test_parseargs_error_msg(
+        &["--args", "files..."],
+        "parseargs: Shell sh does not support arrays, so variadic argument FILES is not supported",
+    );
+    exec::test_parseargs_error_msg(
+        &["-o", "s:src=src", "--args", "src"],
+        "parseargs: Duplicate usage of variable/function 'src'",
+    );
+    exec::test_parseargs_error_msg(
+        &["--args", "src,dst?,file"],
+        "parseargs: Error parsing argument definition:\nsrc,dst?,file\n            ^\n            Required argument after optional argument",
+    );
+}
+
+#[test]
+fn test_argument_count() {
+    exec::test_code_gen(
+        &["--min-args", "1", "--max-args", "2"],
+        &["a", "b"],
+        &["set -- 'a' 'b'"],
+    );
+    exec::test_error_msg(
+        &["--min-args", "1", "--max-args", "2"],
+        &[],
+        "parseargs: Too few arguments: 0 given, at least 1 required",
+    );
+    exec::test_error_msg(
+        &["--min-args", "1", "--max-args", "2"],
+        &["a", "b", "c"],
+        "parseargs: Too many arguments: 3 given, at most 2 allowed",
+    );
+    exec::test_error_msg(&["--no-args"], &["a"], "parseargs: No arguments allowed");
+
+    // arguments after '--' are counted, unless they are collected with --remainder
+    exec::test_error_msg(
+        &["--no-args"],
+        &["--", "a"],
+        "parseargs: No arguments allowed",
+    );
+    exec::test_code_gen(
+        &["-sbash", "--no-args", "-r", "rest"],
+        &["--", "a"],
+        &["typeset -a rest;", "rest=();", "rest+=('a');", "set --"],
+    );
+
+    exec::test_code_gen(
+        &["-sbash", "--max-args", "1", "-a", "arg"],
+        &["a"],
+        &[
+            "if ! typeset -f arg >/dev/null 2>&1; then echo >&2 \"ERROR: Function 'arg' does not exist.\"; exit 127; fi;",
+            "arg 'a' || exit $?;",
+            "set --",
+        ],
+    );
+
+    exec::test_parseargs_error_msg(
+        &["--min-args", "3", "--max-args", "2"],
+        "parseargs: Minimum number of arguments (3) greater than maximum (2)",
+    );
+
+    // arguments collected with --remainder are counted separately
+    let pa_args = [
+        "-sbash",
+        "-r",
+        "rest",
+        "--max-args",
+        "1",
+        "--min-remainder",
+        "1",
+        "--max-remainder",
+        "2",
+    ];
+    exec::test_code_gen(
+        &pa_args,
+        &["a", "--", "b", "c"],
+        &[
+            "typeset -a rest;",
+            "rest=();",
+            "rest+=('b');",
+            "rest+=('c');",
+            "set -- 'a'",
+        ],
+    );
+    exec::test_error_msg(
+        &pa_args,
+        &["a"],
+        "parseargs: Too few arguments after '--': 0 given, at least 1 required",
+    );
+    exec::test_error_msg(
+        &pa_args,
+        &["--", "b", "c", "d"],
+        "parseargs: Too many arguments after '--': 3 given, at most 2 allowed",
+    );
+    exec::test_parseargs_error_msg(
+        &[
+            "-sbash",
+            "-r",
+            "rest",
+            "--min-remainder",
+            "3",
+            "--max-remainder",
+            "2",
+        ],
+        "parseargs: Minimum number of remainder arguments (3) greater than maximum (2)",
+    );
+}
+
+#[test]
+fn test_unknown_options() {
+    exec::test_error_msg(
+        &["--unknown=error", "-o", "x#x"],
+        &["-q"],
+        "parseargs: Unknown option: -q",
+    );
+
+    exec::test_code_gen(
+        &["--unknown=args", "-o", "x#x,v:verbose+verbosity"],
+        &["-v", "--progress", "-xq", "file", "--delete=yes", "-vyz"],
+        &[
+            "verbosity=0;",
+            "verbosity=1;",
+            "x='true';",
+            "verbosity=2;",
+            "set -- '--progress' '-q' 'file' '--delete=yes' '-yz'",
+        ],
+    );
+
+    exec::test_code_gen(
+        &["-sbash", "--unknown=collect:fwd", "-o", "x#x"],
+        &["-xq", "--progress", "file", "--", "-z"],
+        &[
+            "typeset -a fwd;",
+            "fwd=();",
+            "x='true';",
+            "fwd+=('-q');",
+            "fwd+=('--progress');",
+            "set -- 'file' '-z'",
+        ],
+    );
+
+    exec::test_parseargs_error_msg(
+        &["--unknown=collect:fwd"],
+        "parseargs: Shell sh does not support arrays, so option --unknown=collect is not supported",
+    );
+    exec::test_parseargs_error_msg(
+        &["-sbash", "--unknown=collect:fwd", "-o", "f:fwd=fwd"],
+        "parseargs: Duplicate usage of variable/function 'fwd'",
+    );
+}
+
+#[test]
+fn test_forward_options() {
+    exec::test_code_gen(
+        &["-sbash", "-o", "d#debug,x:exec=@cmd..."],
+        &["-d", "file", "--exec", "ls", "-l", "--", "-d"],
+        &[
+            "typeset -a cmd;",
+            "cmd=();",
+            "debug='true';",
+            "cmd+=('ls');",
+            "cmd+=('-l');",
+            "cmd+=('--');",
+            "cmd+=('-d');",
+            "set -- 'file'",
+        ],
+    );
+
+    exec::test_code_gen(
+        &["-sbash", "-o", "d#debug,x:exec=@cmd..."],
+        &["-dxls", "-l"],
+        &[
+            "typeset -a cmd;",
+            "cmd=();",
+            "debug='true';",
+            "cmd+=('ls');",
+            "cmd+=('-l');",
+            "set --",
+        ],
+    );
+
+    exec::test_code_gen(
+        &["-o", "e:exec=run()..."],
+        &["--exec=echo", "don't", "-x"],
+        &[
+            "if ! LC_ALL=C command -V run 2>/dev/null | head -n1 | grep function >/dev/null; then echo >&2 \"ERROR: Function 'run' does not exist.\"; exit 127; fi;",
+            "run 'echo' 'don'\\''t' '-x' || exit $?;",
+            "set --",
+        ],
+    );
+
+    exec::test_error_msg(
+        &["-sbash", "-o", "x:exec=@cmd..."],
+        &["--exec"],
+        "parseargs: Missing argument for: --exec",
+    );
+
+    exec::test_parseargs_error_msg(
+        &["-o", "x:exec=@cmd..."],
+        "parseargs: Shell sh does not support arrays, so array option -x/--exec is not supported",
+    );
+}
+
+#[test]
+fn test_abbreviated_long_options() {
+    let pa_args = [
+        "--abbrev",
+        "-o",
+        "verbose#verbose,verbosity+level,c:color:colour#~color,o:output=out",
+    ];
+
+    exec::test_code_gen(
+        &pa_args,
+        &["--verbose", "--verbosi", "--col", "--outp=file"],
+        &[
+            "level=0;",
+            "verbose='true';",
+            "level=1;",
+            "color='true';",
+            "out='file';",
+            "set --",
+        ],
+    );
+
+    exec::test_code_gen(
+        &pa_args,
+        &["--verbosi=3", "--no-c"],
+        &["level=0;", "level=3;", "color='';", "set --"],
+    );
+
+    exec::test_error_msg(
+        &pa_args,
+        &["--verb"],
+        "parseargs: Ambiguous option: --verb (--verbose, --verbosity)",
+    );
+    exec::test_error_msg(
+        &pa_args,
+        &["--outpu", "x", "--outp", "y"],
+        "parseargs: Duplicate option: --output (-o/--output)",
+    );
+    exec::test_error_msg(&pa_args, &["--x"], "parseargs: Unknown option: --x");
+
+    // not enabled without --abbrev
+    exec::test_error_msg(
+        &["-o", "verbose#verbose"],
+        &["--verb"],
+        "parseargs: Unknown option: --verb",
+    );
+}
+
+#[test]
+fn test_single_dash_long_options() {
+    let pa_args = [
+        "--single-dash=long",
+        "-o",
+        "v:verbose#verbose,o:output=out,d#debug",
+    ];
+
+    exec::test_code_gen(
+        &pa_args,
+        &["-verbose", "-output", "file", "-d"],
+        &["verbose='true';", "out='file';", "debug='true';", "set --"],
+    );
+
+    exec::test_code_gen(
+        &pa_args,
+        &["-output=file", "--verbose"],
+        &["out='file';", "verbose='true';", "set --"],
+    );
+
+    // no clustering of short options
+    exec::test_error_msg(&pa_args, &["-vd"], "parseargs: Unknown option: -vd");
+    exec::test_error_msg(&pa_args, &["-vd=1"], "parseargs: Unknown option: -vd");
+    exec::test_error_msg(&pa_args, &["--vd"], "parseargs: Unknown option: --vd");
+
+    // unknown options are forwarded as given
+    exec::test_code_gen(
+        &[
+            "--single-dash=long",
+            "--unknown=args",
+            "-o",
+            "v:verbose#verbose",
+        ],
+        &["-Xmx512m", "-verbose"],
+        &["verbose='true';", "set -- '-Xmx512m'"],
+    );
+}
+
+#[test]
+fn test_single_dash_mixed_options() {
+    let pa_args = [
+        "--single-dash=mixed",
+        "-o",
+        "verbose#verbose,o:output=out,d#debug,v#vv",
+    ];
+
+    exec::test_code_gen(
+        &pa_args,
+        &["-verbose", "-vd", "-output=file"],
+        &[
+            "verbose='true';",
+            "vv='true';",
+            "debug='true';",
+            "out='file';",
+            "set --",
+        ],
+    );
+
+    exec::test_code_gen(&pa_args, &["-ofile"], &["out='file';", "set --"]);
+}
+
+#[test]
+fn test_plus_options() {
+    let pa_args = [
+        "--plus-options",
+        "-o",
+        "x#trace,v#verbose,e#errexit(),o=out",
+    ];
+
+    exec::test_code_gen(
+        &pa_args,
+        &["-x", "+v", "+e", "one"],
+        &[
+            &sh_func_check("errexit"),
+            "trace='true';",
+            "verbose='';",
+            "errexit '' || exit $?;",
+            "set -- 'one'",
+        ],
+    );
+
+    exec::test_code_gen(
+        &pa_args,
+        &["+xv", "-e"],
+        &[
+            &sh_func_check("errexit"),
+            "trace='';",
+            "verbose='';",
+            "errexit 'true' || exit $?;",
+            "set --",
+        ],
+    );
+
+    exec::test_error_msg(&pa_args, &["+o", "file"], "parseargs: Unknown option: +o");
+
+    // a flag can be toggled, the last occurrence wins
+    exec::test_code_gen(
+        &pa_args,
+        &["-x", "+x", "-x"],
+        &[
+            &sh_func_check("errexit"),
+            "trace='true';",
+            "trace='';",
+            "trace='true';",
+            "set --",
+        ],
+    );
+    exec::test_error_msg(
+        &pa_args,
+        &["-x", "-x"],
+        "parseargs: Duplicate option: -x (-x)",
+    );
+    exec::test_error_msg(
+        &pa_args,
+        &["+x", "+x"],
+        "parseargs: Duplicate option: +x (-x)",
+    );
+
+    // not enabled
+    exec::test_code_gen(&["-o", "x#trace"], &["+x"], &["set -- '+x'"]);
+}
+
+#[test]
+fn test_ignore_case() {
+    let pa_args = [
+        "--ignore-case",
+        "-o",
+        "verbose#verbose,c:color#~color,f:format=format=L=json|yaml,V#version",
+    ];
+
+    exec::test_code_gen(
+        &pa_args,
+        &["--Verbose", "--FORMAT=YAML", "--No-Color", "-V"],
+        &[
+            "verbose='true';",
+            "format='yaml';",
+            "color='';",
+            "version='true';",
+            "set --",
+        ],
+    );
+
+    // short options are still case-sensitive
+    exec::test_error_msg(&pa_args, &["-F", "json"], "parseargs: Unknown option: -F");
+
+    exec::test_code_gen(
+        &["--ignore-case", "--abbrev", "-o", "verbose#verbose"],
+        &["--VERB"],
+        &["verbose='true';", "set --"],
+    );
+
+    // single dash long options are also matched case-insensitive
+    exec::test_code_gen(
+        &[
+            "--ignore-case",
+            "--single-dash=mixed",
+            "-o",
+            "v:verbose#verbose,b#brief",
+        ],
+        &["-Verbose", "-b"],
+        &["verbose='true';", "brief='true';", "set --"],
+    );
+    exec::test_code_gen(
+        &["--ignore-case", "--single-dash=long", "-o", "o:output=out"],
+        &["-OUTPUT=file"],
+        &["out='file';", "set --"],
+    );
+
+    // not enabled
+    exec::test_error_msg(
+        &["-o", "verbose#verbose"],
+        &["--Verbose"],
+        "parseargs: Unknown option: --Verbose",
+    );
+}
+
+#[test]
+fn test_numeric_options() {
+    let pa_args = ["-o", "n:#:lines=lines=U,v#verbose"];
+
+    exec::test_code_gen(&pa_args, &["-20", "file"], &["lines=20;", "set -- 'file'"]);
+    // a numeric option can't be combined with short options
+    exec::test_error_msg(&pa_args, &["-v5"], "parseargs: Unknown option: -5");
+    exec::test_error_msg(
+        &pa_args,
+        &["-n", "5", "-3"],
+        "parseargs: Duplicate option: -3 (-n/--lines/-NUM)",
+    );
+
+    // without numeric option digits are short options
+    exec::test_error_msg(
+        &["-o", "n=lines"],
+        &["-20"],
+        "parseargs: Unknown option: -2",
+    );
+}
+
+#[test]
+fn test_negative_number_values() {
+    let pa_args = ["-o", "o:offset=offset=I,d:delta=delta?=1=I"];
+
+    exec::test_code_gen(&pa_args, &["--offset", "-1"], &["offset=-1;", "set --"]);
+    exec::test_code_gen(&pa_args, &["-o", "-1"], &["offset=-1;", "set --"]);
+    exec::test_code_gen(&pa_args, &["--offset=-1"], &["offset=-1;", "set --"]);
+    exec::test_code_gen(&pa_args, &["-o-1"], &["offset=-1;", "set --"]);
+    exec::test_code_gen(&pa_args, &["-d-3"], &["delta=-3;", "set --"]);
+    exec::test_code_gen(&pa_args, &["--delta=-3"], &["delta=-3;", "set --"]);
+    exec::test_code_gen(&pa_args, &["--delta"], &["delta=1;", "set --"]);
+
+    // works also with a numeric option
+    exec::test_code_gen(
+        &["-o", "o:offset=offset=I,#=lines"],
+        &["--offset", "-1", "-5"],
+        &["offset=-1;", "lines='5';", "set --"],
+    );
+}
+
+#[test]
+fn test_map_options() {
+    let pa_args = ["-sbash", "-o", "D:define=~%props,P=%prop()"];
+
+    exec::test_code_gen(
+        &pa_args,
+        &["-Dprofile=prod", "--define", "region=eu=west", "-P", "a="],
+        &[
+            "if ! typeset -f prop >/dev/null 2>&1; then echo >&2 \"ERROR: Function 'prop' does not exist.\"; exit 127; fi;",
+            "typeset -A props;",
+            "props=();",
+            "props['profile']='prod';",
+            "props['region']='eu=west';",
+            "prop 'a' '' || exit $?;",
+            "set --",
+        ],
+    );
+
+    exec::test_code_gen(
+        &pa_args,
+        &["-Dx=1", "--no-define"],
+        &[
+            "if ! typeset -f prop >/dev/null 2>&1; then echo >&2 \"ERROR: Function 'prop' does not exist.\"; exit 127; fi;",
+            "typeset -A props;",
+            "props=();",
+            "props['x']='1';",
+            "props=();",
+            "set --",
+        ],
+    );
+
+    exec::test_error_msg(
+        &pa_args,
+        &["-Dprofile"],
+        "parseargs: Invalid value for -D/--[no-]define: 'profile' (expected KEY=VALUE)",
+    );
+    exec::test_error_msg(
+        &pa_args,
+        &["-D=prod"],
+        "parseargs: Invalid value for -D/--[no-]define: '=prod' (expected KEY=VALUE)",
+    );
+    exec::test_error_msg(&pa_args, &["-D"], "parseargs: Missing argument for: -D");
+
+    exec::test_code_gen(
+        &["-sksh", "-o", "D=%props"],
+        &["-Da=b"],
+        &[
+            "typeset -A props;",
+            "unset props; typeset -A props;",
+            "props['a']='b';",
+            "set --",
+        ],
+    );
+
+    // replacement markers in key and value are kept as given
+    exec::test_code_gen(
+        &["-sbash", "-o", "D=%props"],
+        &[
+            "-D",
+            "k;echo PWNED #={KEY}",
+            "-D",
+            "{KEY}{NAME}={NAME}{VALUE}",
+        ],
+        &[
+            "typeset -A props;",
+            "props=();",
+            "props['k;echo PWNED #']='{KEY}';",
+            "props['{KEY}{NAME}']='{NAME}{VALUE}';",
+            "set --",
+        ],
+    );
+
+    exec::test_parseargs_error_msg(
+        &["-o", "D=%props"],
+        "parseargs: Shell sh does not support associative arrays, so map option -D is not supported",
+    );
+}
+
+#[test]
+fn test_separated_values() {
+    let pa_args = ["-sbash", "-o", "t:tags=@tags/\\,,p:path=@path/':'=G=/*"];
+
+    exec::test_code_gen(
+        &pa_args,
+        &["--tags=a b,*,,c\\,d", "-t", "e", "-p", "/bin:/usr/bin"],
+        &[
+            "typeset -a tags;",
+            "tags=();",
+            "typeset -a path;",
+            "path=();",
+            "tags+=('a b');",
+            "tags+=('*');",
+            "tags+=('c,d');",
+            "tags+=('e');",
+            "path+=('/bin');",
+            "path+=('/usr/bin');",
+            "set --",
+        ],
+    );
+
+    exec::test_error_msg(
+        &pa_args,
+        &["-p", "/bin:usr/bin"],
+        "parseargs: Invalid value for -p/--path: 'usr/bin' (must match pattern '/*')",
+    );
+
+    exec::test_code_gen(
+        &["-o", "t:tags=tag()/;"],
+        &["-ta;b"],
+        &[
+            &sh_func_check("tag"),
+            "tag 'a' || exit $?;",
+            "tag 'b' || exit $?;",
+            "set --",
+        ],
+    );
+
+    exec::test_parseargs_env(
+        &[("TAGS", "x,y")],
+        &["-sbash", "-o", "t:tags=@tags/\\,@TAGS"],
+        &[],
+        0,
+        &[
+            "typeset -a tags;",
+            "tags=();",
+            "tags+=('x');",
+            "tags+=('y');",
+            "set --",
+        ],
+        &[],
+    );
+}
+
+#[test]
+fn test_sub_options() {
+    let pa_args = [
+        "-o",
+        "o:options=opts{ro#mode=ro,rw#mode=rw!,uid=uid=U,noatime#noatime,c=codepage?='437'}",
+    ];
+
+    exec::test_code_gen(
+        &pa_args,
+        &["-o", "ro,uid=1000,noatime,c", "file"],
+        &[
+            "mode='rw';",
+            "opts='ro,uid=1000,noatime,c';",
+            "mode='ro';",
+            "uid=1000;",
+            "noatime='true';",
+            "codepage='437';",
+            "set -- 'file'",
+        ],
+    );
+
+    exec::test_code_gen(
+        &pa_args,
+        &["--options=noatime=no,,c=850"],
+        &[
+            "mode='rw';",
+            "opts='noatime=no,,c=850';",
+            "noatime='';",
+            "codepage='850';",
+            "set --",
+        ],
+    );
+
+    exec::test_error_msg(
+        &pa_args,
+        &["-o", "ro,rw"],
+        "parseargs: Options are mutual exclusive: ro, rw (sub-option of -o/--options)",
+    );
+    exec::test_error_msg(
+        &pa_args,
+        &["-o", "sync"],
+        "parseargs: Unknown sub-option 'sync' of -o/--options",
+    );
+    exec::test_error_msg(
+        &pa_args,
+        &["-o", "uid=1,uid=2"],
+        "parseargs: Duplicate sub-option 'uid' of -o/--options",
+    );
+    exec::test_error_msg(
+        &pa_args,
+        &["-o", "uid"],
+        "parseargs: Missing value for sub-option 'uid' of -o/--options",
+    );
+    exec::test_error_msg(
+        &pa_args,
+        &["-o", "uid=root"],
+        "parseargs: Invalid unsigned integer value for sub-option 'uid' of -o/--options: 'root'",
+    );
+    exec::test_error_msg(
+        &pa_args,
+        &["-o", "noatime=maybe"],
+        "parseargs: Invalid boolean value for sub-option 'noatime' of -o/--options: 'maybe'",
+    );
+    exec::test_error_msg(
+        &pa_args,
+        &["-o", "ro=yes"],
+        "parseargs: No value supported for sub-option 'ro' of -o/--options",
+    );
+
+    // unquoted values at the end of the sub-option list
+    exec::test_code_gen(&["-o", "o=opts{x=x:=foo}"], &[], &["x='foo';", "set --"]);
+    exec::test_code_gen(
+        &["-o", "o=opts{x=x?=on}"],
+        &["-o", "x"],
+        &["opts='x';", "x='on';", "set --"],
+    );
+    exec::test_code_gen(
+        &["-o", "o=opts{x=x=R=[a-z]+}"],
+        &["-o", "x=abc"],
+        &["opts='x=abc';", "x='abc';", "set --"],
+    );
+    exec::test_error_msg(
+        &["-o", "o=opts{x=x=R=[a-z]+}"],
+        &["-o", "x=1"],
+        "parseargs: Invalid value for sub-option 'x' of -o: '1' (must match regex '[a-z]+')",
+    );
+    exec::test_code_gen(
+        &["-o", "o=opts{x=x=R=^a{2\\}$}"],
+        &["-o", "x=aa"],
+        &["opts='x=aa';", "x='aa';", "set --"],
+    );
+    exec::test_code_gen(
+        &["-o", "o=opts{x=x=G=*.c}"],
+        &["-o", "x=main.c"],
+        &["opts='x=main.c';", "x='main.c';", "set --"],
+    );
+    exec::test_error_msg(
+        &["-o", "o=opts{x=x=G=*.c}"],
+        &["-o", "x=main.h"],
+        "parseargs: Invalid value for sub-option 'x' of -o: 'main.h' (must match pattern '*.c')",
+    );
+
+    // required sub-options are only checked, if the option is given
+    let pa_args = ["-o", "o=opts{u:user=*user}"];
+    exec::test_code_gen(&pa_args, &[], &["set --"]);
+    exec::test_code_gen(
+        &pa_args,
+        &["-o", "u=root"],
+        &["opts='u=root';", "user='root';", "set --"],
+    );
+    exec::test_error_msg(
+        &pa_args,
+        &["-o", ""],
+        "parseargs: Required option not found: u/user (sub-option of -o)",
+    );
+
+    // with an array target every occurrence is checked on its own
+    let pa_args = [
+        "-sbash",
+        "-o",
+        "o=@opts{ro#mode=ro,rw#mode=rw,u:user=*user}",
+    ];
+    exec::test_code_gen(
+        &pa_args,
+        &["-o", "ro,u=root", "-o", "rw,u=guest"],
+        &[
+            "typeset -a opts;",
+            "opts=();",
+            "opts+=('ro,u=root');",
+            "mode='ro';",
+            "user='root';",
+            "opts+=('rw,u=guest');",
+            "mode='rw';",
+            "user='guest';",
+            "set --",
+        ],
+    );
+    exec::test_error_msg(
+        &pa_args,
+        &["-o", "ro,u=root", "-o", "rw"],
+        "parseargs: Required option not found: u/user (sub-option of -o)",
+    );
+    exec::test_error_msg(
+        &pa_args,
+        &["-o", "ro,u=root", "-o", "u=a,u=b"],
+        "parseargs: Duplicate sub-option 'u' of -o",
+    );
+}
+
+#[test]
+fn test_response_files() {
+    let dir = std::env::temp_dir().join(format!("parseargs-code-gen-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let rsp_file = dir.join("args.txt");
+    std::fs::write(
+        &rsp_file,
+        "# options generated by CI\n-v\n--file 'my file.txt' # with blank\n\"it's\"\n",
+    )
+    .unwrap();
+    let rsp_arg = format!("@{}", rsp_file.display());
+
+    let pa_args = ["--response-files", "-o", "v#verbose,f:file=file"];
+
+    exec::test_code_gen(
+        &pa_args,
+        &[&rsp_arg, "last"],
+        &[
+            "verbose='true';",
+            "file='my file.txt';",
+            "set -- 'it'\\''s' 'last'",
+        ],
+    );
+    // not expanded after '--' and without --response-files
+    exec::test_code_gen(
+        &pa_args,
+        &["--", &rsp_arg],
+        &[&format!("set -- '{}'", rsp_arg)],
+    );
+    exec::test_code_gen(
+        &pa_args[1..],
+        &[&rsp_arg],
+        &[&format!("set -- '{}'", rsp_arg)],
+    );
+
+    let missing = dir.join("missing.txt");
+    exec::test_error_msg(
+        &pa_args,
+        &[&format!("@{}", missing.display())],
+        &format!(
+            "parseargs: Can't read response file '{}': No such file or directory (os error 2)",
+            missing.display()
+        ),
+    );
+
+    std::fs::write(&rsp_file, "-v 'unterminated\n").unwrap();
+    exec::test_error_msg(
+        &pa_args,
+        &[&rsp_arg],
+        &format!(
+            "parseargs: Unterminated single quote in response file '{}'",
+            rsp_file.display()
+        ),
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_args_from_stdin0() {
+    let pa_args = ["--args-from-stdin0", "-o", "v#verbose,f:file=file"];
+
+    exec::test_parseargs_stdin(
+        &pa_args,
+        b"-v\0--file\0my file.txt\0\0--\0--help\0-o\0",
+        0,
+        &[
+            "verbose='true';",
+            "file='my file.txt';",
+            "set -- '' '--help' '-o'",
+        ],
+        &[],
+    );
+    // last terminator is optional
+    exec::test_parseargs_stdin(
+        &pa_args,
+        b"-v\0last",
+        0,
+        &["verbose='true';", "set -- 'last'"],
+        &[],
+    );
+    // no input is no argument (like `for a in "$@"; do printf '%s\0' "$a"; done` with `$# == 0`)
+    exec::test_parseargs_stdin(&pa_args, b"", 0, &["set --"], &[]);
+    exec::test_parseargs_stdin(
+        &["--args-from-stdin0", "--no-args"],
+        b"",
+        0,
+        &["set --"],
+        &[],
+    );
+    // a single NUL is one empty argument
+    exec::test_parseargs_stdin(&pa_args, b"\0", 0, &["set -- ''"], &[]);
+
+    exec::test_parseargs_stdin(
+        &pa_args,
+        b"-f\0\xc3\x28\0",
+        1,
+        &["exit 1"],
+        &["parseargs: Invalid UTF-8 char(s) in \"\u{FFFD}(\""],
+    );
+}
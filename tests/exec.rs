@@ -4,7 +4,46 @@ fn parseargs() -> Command {
     Command::cargo_bin("parseargs").unwrap()
 }
 
-fn test_parseargs(
+/// Test the generated code and error messages.
+///
+///  # Arguments
+/// * `pa_args` - parseargs arguments (before the `--`)
+/// * `script_args` - script arguments (after the `--`)
+/// * `exit_code` - the expected exit code of parseargs
+/// * `code_lines` - the expected shell code lines
+/// * `error_lines` - the expected lines on stderr
+///
+#[allow(dead_code)] // because it is not used in ALL tests
+pub fn test_parseargs(
+    pa_args: &[&str],
+    script_args: &[&str],
+    exit_code: i32,
+    code_lines: &[&str],
+    error_lines: &[&str],
+) {
+    test_parseargs_env(
+        &[],
+        pa_args,
+        script_args,
+        exit_code,
+        code_lines,
+        error_lines,
+    )
+}
+
+/// Test the generated code and error messages with additional environment variables.
+///
+///  # Arguments
+/// * `env` - environment variables (name, value) set for parseargs
+/// * `pa_args` - parseargs arguments (before the `--`)
+/// * `script_args` - script arguments (after the `--`)
+/// * `exit_code` - the expected exit code of parseargs
+/// * `code_lines` - the expected shell code lines
+/// * `error_lines` - the expected lines on stderr
+///
+#[allow(dead_code)] // because it is not used in ALL tests
+pub fn test_parseargs_env(
+    env: &[(&str, &str)],
     pa_args: &[&str],
     script_args: &[&str],
     exit_code: i32,
@@ -24,6 +63,7 @@ fn test_parseargs(
     }
 
     parseargs()
+        .envs(env.iter().copied())
         .args(pa_args)
         .arg("--")
         .args(script_args)
@@ -33,6 +73,45 @@ fn test_parseargs(
         .stdout(expected_code);
 }
 
+/// Test the generated code and error messages with script arguments read from STDIN.
+///
+///  # Arguments
+/// * `pa_args` - parseargs arguments (before the `--`)
+/// * `input` - the data written to STDIN of parseargs
+/// * `exit_code` - the expected exit code of parseargs
+/// * `code_lines` - the expected shell code lines
+/// * `error_lines` - the expected lines on stderr
+///
+#[allow(dead_code)] // because it is not used in ALL tests
+pub fn test_parseargs_stdin(
+    pa_args: &[&str],
+    input: &[u8],
+    exit_code: i32,
+    code_lines: &[&str],
+    error_lines: &[&str],
+) {
+    let mut expected_code = String::new();
+    for code_line in code_lines {
+        expected_code.push_str(code_line);
+        expected_code.push('\n');
+    }
+
+    let mut expected_error_msg = String::new();
+    for error_line in error_lines {
+        expected_error_msg.push_str(error_line);
+        expected_error_msg.push('\n');
+    }
+
+    parseargs()
+        .args(pa_args)
+        .arg("--")
+        .write_stdin(input)
+        .assert()
+        .code(exit_code)
+        .stderr(expected_error_msg)
+        .stdout(expected_code);
+}
+
 /// Test the generated code.
 ///
 ///  # Arguments
@@ -52,6 +52,106 @@ fn test_parser_errors() {
     test_parser_error_msg("\\=d#debug", 0, "'=' not allowed here");
     test_parser_error_msg("x\\=d#debug", 2, "'=' not allowed here");
     test_parser_error_msg("x=d#debug", 3, "Unexpected character '#'");
+    test_parser_error_msg("f=format=", 8, "value restriction expected after this");
+    test_parser_error_msg("f=format=X=a|b", 9, "Unknown value restriction 'X'");
+    test_parser_error_msg("f=format=L", 9, "'=' expected after this");
+    test_parser_error_msg("f=format=L=a|", 12, "name expected after this");
+    test_parser_error_msg("f#format=L=a|", 10, "Unexpected character '='");
+    test_parser_error_msg("f=format=R=", 10, "regular expression expected after this");
+    test_parser_error_msg(
+        "f=format=R=[a-z",
+        14,
+        "Invalid regular expression: found unclosed character class",
+    );
+    test_parser_error_msg(
+        "f=format=R=(a",
+        12,
+        "Invalid regular expression: found open group without closing ')'",
+    );
+    test_parser_error_msg("f=file=G=", 8, "glob pattern expected after this");
+    test_parser_error_msg("c#mode='copy", 7, "Unterminated quoted string");
+    test_parser_error_msg("c#mode=\"copy\\\"", 7, "Unterminated quoted string");
+    test_parser_error_msg("f=format=L=a|'b", 13, "Unterminated quoted string");
+    test_parser_error_msg("o=output:=", 9, "default value expected after this");
+    test_parser_error_msg("o=output:='-", 10, "Unterminated quoted string");
+    test_parser_error_msg("o=output:= ", 9, "default value expected after this");
+    test_parser_error_msg("d#debug!", 7, "Unexpected character '!'");
+    test_parser_error_msg("c=color?=", 8, "fallback value expected after this");
+    test_parser_error_msg("c=color?", 7, "Unexpected character '?'");
+    test_parser_error_msg("I=@", 2, "name expected after this");
+    test_parser_error_msg("I=@includes()", 11, "Unexpected character '('");
+    test_parser_error_msg("I#@includes", 1, "name expected after this");
+    test_parser_error_msg("r=retries=5", 10, "'<' expected after this");
+    test_parser_error_msg("r=retries=x5<10", 11, "Unknown value restriction 'x5'");
+    test_parser_error_msg("r=retries=I=1.5<10", 14, "Invalid number '1.5'");
+    test_parser_error_msg(
+        "r=retries=10<1",
+        13,
+        "Invalid range: lower bound greater than upper bound",
+    );
+    test_parser_error_msg(
+        "t=timeout=F=NaN<",
+        15,
+        "Invalid range: bounds must be finite numbers",
+    );
+    test_parser_error_msg(
+        "t=timeout=F=0<inf",
+        16,
+        "Invalid range: bounds must be finite numbers",
+    );
+    test_parser_error_msg(
+        "c:copy#~mode=copy",
+        12,
+        "Negation ('~') not supported for mode switch",
+    );
+    test_parser_error_msg(
+        "v+~verbosity",
+        2,
+        "Negation ('~') not supported for counter",
+    );
+    test_parser_error_msg("d#~*debug", 2, "name expected after this");
+    test_parser_error_msg(
+        "t:token=token@",
+        13,
+        "environment variable name expected after this",
+    );
+    test_parser_error_msg(
+        "x:exec=cmd...",
+        12,
+        "Forwarding ('...') requires an array or a function",
+    );
+    test_parser_error_msg("x:exec=@cmd...:=ls", 14, "Unexpected character ':'");
+    test_parser_error_msg("D=%", 2, "name expected after this");
+    test_parser_error_msg("o=opts{ro#ro", 11, "'}' expected after this");
+    test_parser_error_msg(
+        "o=opts{v+verbose}",
+        16,
+        "Only flags and assignments are supported as sub-options",
+    );
+    test_parser_error_msg(
+        "o=opts{ro#~ro}",
+        13,
+        "Environment variables, negation, singletons and numeric options are not supported for sub-options",
+    );
+    test_parser_error_msg(
+        "t:tags=tags/;",
+        12,
+        "Separator ('/') requires an array or a function",
+    );
+    test_parser_error_msg("t:tags=@tags/", 12, "separator expected after this");
+    test_parser_error_msg("t:tags=@tags/,x#x", 12, "separator expected after this");
+    test_parser_error_msg("D=%props:=a=b", 8, "Unexpected character ':'");
+    test_parser_error_msg("D#%props", 1, "name expected after this");
+    test_parser_error_msg(
+        "D:def=~%p()",
+        10,
+        "Negation ('~') not supported for map options with function",
+    );
+    test_parser_error_msg(
+        "c#mode@MODE=copy",
+        11,
+        "Environment variable not supported for mode switch",
+    );
 }
 
 #[test]
@@ -81,4 +181,68 @@ fn test_validation_errors() {
         "c#mode=copy,m#mode=copy",
         "Duplicate value 'copy' for mode 'mode'",
     );
+    test_validation_error_msg(
+        "c#mode=copy!,m#mode=move!",
+        "Multiple default values for mode 'mode'",
+    );
+    test_validation_error_msg(
+        "o=output():=out",
+        "Default value not supported for function 'output'",
+    );
+    test_validation_error_msg(
+        "I=@includes:=/usr/include",
+        "Default value not supported for array 'includes'",
+    );
+    test_validation_error_msg(
+        "c=color?=sometimes=L=always|never",
+        "Fallback value rejected: Invalid value for -c: 'sometimes' (allowed: always, never)",
+    );
+    test_validation_error_msg(
+        "f=format:=xml=L=json|yaml",
+        "Default value rejected: Invalid value for -f: 'xml' (allowed: json, yaml)",
+    );
+    test_validation_error_msg("d#~debug", "Negation requires a long option: -d");
+    test_validation_error_msg(
+        "o=opts{u:user=user},u:user=user",
+        "Duplicate usage of variable/function 'user'",
+    );
+    test_validation_error_msg(
+        "o=opts{u:user=user},x=xopts{u#user}",
+        "Duplicate usage of variable/function 'user'",
+    );
+    test_validation_error_msg(
+        "c:color#~color,no-color#plain",
+        "Negated option '--no-color' clashes with explicitly defined option",
+    );
+    test_validation_error_msg(
+        "no-color#plain,c:color#~color",
+        "Negated option '--no-color' clashes with explicitly defined option",
+    );
+}
+
+#[test]
+fn test_ignore_case_validation_errors() {
+    exec::test_parseargs_error_msg(
+        &["--ignore-case", "-o", "verbose#verbose,Verbose#loud"],
+        "parseargs: Duplicate definition of option '--Verbose'",
+    );
+    exec::test_parseargs_error_msg(
+        &["--ignore-case", "-o", "c:color#~color,No-Color#plain"],
+        "parseargs: Negated option '--no-color' clashes with explicitly defined option",
+    );
+}
+
+#[test]
+fn test_numeric_option_errors() {
+    test_parser_error_msg("#debug", 0, "Expected #, = or + after this");
+    test_parser_error_msg(
+        "v:##verbose",
+        10,
+        "Numeric option ('#') only supported for assignments",
+    );
+    test_validation_error_msg("#=lines,#=count", "Duplicate definition of numeric option");
+    test_validation_error_msg(
+        "#=lines,5#five",
+        "Short option '-5' clashes with numeric option",
+    );
 }